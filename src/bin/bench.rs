@@ -0,0 +1,152 @@
+//! Plays a bot through seeded games and prints a json report, so evaluator and search changes can
+//! be compared on the same pieces.
+//!
+//! `cargo run --release --no-default-features --bin bench -- [options]`
+//!
+//! --bot NAME   bot to benchmark (beam)
+//! --games N    (10)
+//! --seed N     seed of the first game, the others count up from it (0)
+//! --pieces N   stop every game after this many pieces, unlimited by default
+
+use std::time::Instant;
+
+use anyhow::{Context, bail};
+use serde::Serialize;
+use tetrust_wgpu::{
+    bot::{self, game},
+    tetris::Tetris,
+};
+
+struct Options {
+    bot: String,
+    games: u64,
+    seed: u64,
+    pieces: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            bot: "beam".into(),
+            games: 10,
+            seed: 0,
+            pieces: None,
+        }
+    }
+}
+
+fn parse_options() -> anyhow::Result<Options> {
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .with_context(|| format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--bot" => options.bot = value,
+            "--games" => options.games = value.parse()?,
+            "--seed" => options.seed = value.parse()?,
+            "--pieces" => options.pieces = Some(value.parse()?),
+            _ => bail!("unknown option {arg}"),
+        }
+    }
+    if bot::by_name(&options.bot).is_none() {
+        bail!(
+            "unknown bot {}, expected one of {:?}",
+            options.bot,
+            bot::BOTS
+        );
+    }
+    if options.games == 0 {
+        bail!("need at least one game");
+    }
+    Ok(options)
+}
+
+#[derive(Debug, Serialize)]
+struct Stat {
+    mean: f64,
+    median: f64,
+}
+
+impl Stat {
+    fn new(mut values: Vec<f64>) -> Self {
+        values.sort_by(f64::total_cmp);
+        let mid = values.len() / 2;
+        let median = if values.len().is_multiple_of(2) {
+            (values[mid - 1] + values[mid]) / 2.
+        } else {
+            values[mid]
+        };
+
+        Self {
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            median,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    bot: String,
+    games: u64,
+    seed: u64,
+    max_pieces: Option<usize>,
+    lines: Stat,
+    score: Stat,
+    pieces: Stat,
+    /// pieces placed per second of wall clock time, thinking included
+    pps: Stat,
+    /// share of the cleared lines that came from tetrises
+    tetris_rate: Stat,
+    perfect_clears: Stat,
+    max_height: Stat,
+    /// share of the games that lasted until the piece cap
+    survival: f64,
+}
+
+fn main() -> anyhow::Result<()> {
+    let options = parse_options()?;
+
+    let mut outcomes = vec![];
+    for game in 0..options.games {
+        let mut tetris = Tetris::with_seed(options.seed.wrapping_add(game));
+        // no time limit, so the games don't depend on how fast the machine is
+        let mut bot = bot::by_name_with_budget(&options.bot, None)
+            .expect("bot was checked when parsing options");
+
+        let start = Instant::now();
+        let outcome = game::play(bot.as_mut(), &mut tetris, options.pieces);
+        outcomes.push((outcome, start.elapsed().as_secs_f64()));
+    }
+
+    let stat = |f: &dyn Fn(&game::Outcome, f64) -> f64| {
+        Stat::new(
+            outcomes
+                .iter()
+                .map(|(outcome, secs)| f(outcome, *secs))
+                .collect(),
+        )
+    };
+    let report = Report {
+        lines: stat(&|x, _| x.lines as f64),
+        score: stat(&|x, _| x.score as f64),
+        pieces: stat(&|x, _| x.pieces as f64),
+        pps: stat(&|x, secs| x.pieces as f64 / secs),
+        tetris_rate: stat(&|x, _| match x.lines {
+            0 => 0.,
+            lines => (x.tetrises * 4) as f64 / lines as f64,
+        }),
+        perfect_clears: stat(&|x, _| x.perfect_clears as f64),
+        max_height: stat(&|x, _| x.max_height as f64),
+        survival: outcomes.iter().filter(|x| !x.0.topped_out).count() as f64
+            / outcomes.len() as f64,
+        bot: options.bot,
+        games: options.games,
+        seed: options.seed,
+        max_pieces: options.pieces,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
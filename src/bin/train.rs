@@ -0,0 +1,215 @@
+//! Tunes the evaluator weights with a genetic algorithm. Every generation each weight set plays the
+//! same seeded games, the best ones are kept and the rest are bred from them. Each generation's
+//! winner also plays a set of games none of them trained on, and is written out if it does better
+//! there than every winner before it. The game picks the weights up on launch.
+//!
+//! `cargo run --release --no-default-features --bin train -- [options]`
+//!
+//! --population N   weight sets per generation (24)
+//! --generations N  (20)
+//! --games N        games each weight set plays per generation (4)
+//! --pieces N       piece cap per game (500)
+//! --bot NAME       bot the weights are plugged into (greedy)
+//! --seed N         seeds the games and the algorithm itself (0)
+//! --out PATH       where to write the weights (tetrust_weights.json)
+
+use std::thread;
+
+use anyhow::{Context, bail};
+use rand::{Rng, SeedableRng, seq::IndexedRandom};
+use rand_chacha::ChaCha8Rng;
+use tetrust_wgpu::{
+    bot::{self, game, weights},
+    tetris::{
+        Tetris,
+        board::{Board, features::FEATURES},
+    },
+};
+
+type Weights = [f32; FEATURES];
+
+/// how much a mutated weight can move by, weights are kept at length 1
+const MUTATION: f32 = 0.2;
+const MUTATION_CHANCE: f64 = 0.3;
+const TOURNAMENT: usize = 3;
+
+struct Options {
+    population: usize,
+    generations: usize,
+    games: u64,
+    pieces: usize,
+    bot: String,
+    seed: u64,
+    out: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            population: 24,
+            generations: 20,
+            games: 4,
+            pieces: 500,
+            bot: "greedy".into(),
+            seed: 0,
+            out: weights::WEIGHTS_PATH.into(),
+        }
+    }
+}
+
+fn parse_options() -> anyhow::Result<Options> {
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .with_context(|| format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--population" => options.population = value.parse()?,
+            "--generations" => options.generations = value.parse()?,
+            "--games" => options.games = value.parse()?,
+            "--pieces" => options.pieces = value.parse()?,
+            "--bot" => options.bot = value,
+            "--seed" => options.seed = value.parse()?,
+            "--out" => options.out = value,
+            _ => bail!("unknown option {arg}"),
+        }
+    }
+    if bot::by_name(&options.bot).is_none() {
+        bail!(
+            "unknown bot {}, expected one of {:?}",
+            options.bot,
+            bot::BOTS
+        );
+    }
+    if options.population < 2 {
+        bail!("population has to be at least 2");
+    }
+    Ok(options)
+}
+
+fn main() -> anyhow::Result<()> {
+    let options = parse_options()?;
+    let mut rng = ChaCha8Rng::seed_from_u64(options.seed);
+
+    let start = normalized(Board::default().weights());
+    let mut population = vec![start];
+    while population.len() < options.population {
+        population.push(mutated(&start, &mut rng));
+    }
+
+    // the games after the last generation's, so the winners are compared on the same ones
+    let judge_seed = options
+        .seed
+        .wrapping_add(options.generations as u64 * options.games);
+    let mut best = (f32::MIN, start);
+    for generation in 0..options.generations {
+        let seed = options.seed.wrapping_add(generation as u64 * options.games);
+        let mut ranked = evaluate(&population, &options, seed);
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mean = ranked.iter().map(|x| x.0).sum::<f32>() / ranked.len() as f32;
+        let judged = fitness(&ranked[0].1, &options, judge_seed);
+        println!(
+            "generation {generation}: best {:.1} lines ({judged:.1} on the judging games), mean \
+             {mean:.1}",
+            ranked[0].0
+        );
+        if judged >= best.0 {
+            best = (judged, ranked[0].1);
+            weights::save(&options.out, &best.1)?;
+        }
+
+        population = breed(&ranked, options.population, &mut rng);
+    }
+
+    println!(
+        "best {:.1} lines on the judging games, written to {}",
+        best.0, options.out
+    );
+    Ok(())
+}
+
+/// plays every weight set on all cores, returning (mean lines, weights)
+fn evaluate(population: &[Weights], options: &Options, seed: u64) -> Vec<(f32, Weights)> {
+    let threads = thread::available_parallelism().map_or(1, |x| x.get());
+    let chunk = population.len().div_ceil(threads);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = population
+            .chunks(chunk)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|weights| (fitness(weights, options, seed), *weights))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("training thread panicked"))
+            .collect()
+    })
+}
+
+/// the mean lines cleared over `options.games` games, seeded from `seed` on
+fn fitness(weights: &Weights, options: &Options, seed: u64) -> f32 {
+    let mut lines = 0;
+    for game in 0..options.games {
+        let mut tetris = Tetris::with_seed(seed.wrapping_add(game));
+        tetris.board.set_weights(*weights);
+        // no time limit, so the games don't depend on how fast the machine is
+        let mut bot = bot::by_name_with_budget(&options.bot, None)
+            .expect("bot was checked when parsing options");
+
+        lines += game::play(bot.as_mut(), &mut tetris, Some(options.pieces)).lines;
+    }
+    lines as f32 / options.games as f32
+}
+
+/// keeps the best quarter and fills the rest with mutated crossovers of tournament winners
+fn breed(ranked: &[(f32, Weights)], size: usize, rng: &mut ChaCha8Rng) -> Vec<Weights> {
+    let elite = (size / 4).max(1);
+    let mut population: Vec<Weights> = ranked.iter().take(elite).map(|x| x.1).collect();
+
+    while population.len() < size {
+        let a = tournament(ranked, rng);
+        let b = tournament(ranked, rng);
+
+        // lean towards the fitter parent
+        let total = a.0 + b.0;
+        let share = if total > 0. { a.0 / total } else { 0.5 };
+        let child = std::array::from_fn(|i| a.1[i] * share + b.1[i] * (1. - share));
+
+        population.push(mutated(&normalized(child), rng));
+    }
+    population
+}
+
+fn tournament<'a>(ranked: &'a [(f32, Weights)], rng: &mut ChaCha8Rng) -> &'a (f32, Weights) {
+    ranked
+        .choose_multiple(rng, TOURNAMENT)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .expect("population is never empty")
+}
+
+fn mutated(weights: &Weights, rng: &mut ChaCha8Rng) -> Weights {
+    let mut weights = *weights;
+    for weight in weights.iter_mut() {
+        if rng.random_bool(MUTATION_CHANCE) {
+            *weight += rng.random_range(-MUTATION..MUTATION);
+        }
+    }
+    normalized(weights)
+}
+
+/// scaling the weights doesn't change which placement a bot picks, so they're kept at length 1
+fn normalized(weights: Weights) -> Weights {
+    let length = weights.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if length == 0. {
+        return weights;
+    }
+    weights.map(|x| x / length)
+}
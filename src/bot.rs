@@ -0,0 +1,157 @@
+pub mod beam;
+pub mod game;
+pub mod heuristic;
+pub mod movegen;
+pub mod pc;
+pub mod weights;
+pub mod worker;
+
+use std::time::Duration;
+
+use crate::tetris::{
+    GameView, Tetris, action::Action, board::Board, tetromino::tetromino_kind::TetrominoKind,
+};
+use beam::BeamBot;
+use heuristic::HeuristicBot;
+use pc::PcBot;
+use worker::Worker;
+
+/// names of every bot, in the order they're cycled through
+pub const BOTS: &[&str] = &["heuristic", "greedy", "beam", "pc"];
+
+/// An AI player. It gets to look at the game and answers with the inputs to play for the current
+/// piece, ending with the one that locks it.
+pub trait Bot: Send {
+    fn name(&self) -> &'static str;
+
+    /// returns the actions for the current piece, in the order they should be played
+    fn plan(&mut self, view: &GameView) -> Vec<Action>;
+}
+
+/// creates the bot with the given name, see [`BOTS`]
+pub fn by_name(name: &str) -> Option<Box<dyn Bot>> {
+    by_name_with_budget(name, Some(beam::BUDGET))
+}
+
+/// creates the bot with the given name, with searches cut off after `budget`. Without one they
+/// only stop at their depth and width, so games played headless come out the same every time.
+pub fn by_name_with_budget(name: &str, budget: Option<Duration>) -> Option<Box<dyn Bot>> {
+    let beam = BeamBot {
+        budget,
+        ..Default::default()
+    };
+    match name {
+        "heuristic" => Some(Box::new(HeuristicBot)),
+        "greedy" => Some(Box::new(BeamBot { depth: 1, ..beam })),
+        "beam" => Some(Box::new(beam)),
+        "pc" => Some(Box::new(PcBot { fallback: beam })),
+        _ => None,
+    }
+}
+
+/// Plays a game with a bot, one input every `Rules::autoplay_speed` ticks so it can be watched.
+/// The bot thinks on a `Worker` thread; a plan that arrives after the game has moved on, or that
+/// the game moves away from while it's being played, is thrown away and asked for again.
+pub struct Autoplay {
+    name: &'static str,
+    worker: Worker,
+    // reversed so the next action can be popped off the end
+    plan: Vec<Action>,
+    /// the game the next action in `plan` expects to be played on
+    expected: Option<Fingerprint>,
+    /// id and game state of the request the worker is busy with
+    pending: Option<(u64, Fingerprint)>,
+    next_id: u64,
+    timer: u32,
+}
+
+/// The parts of a game a plan depends on. How far down the piece is isn't one of them, or gravity
+/// faster than `Rules::autoplay_speed` would throw away every plan before it got played.
+#[derive(Debug, Clone, PartialEq)]
+struct Fingerprint {
+    board: Board,
+    kind: TetrominoKind,
+    rotation: u8,
+    x: f32,
+    hold: Option<TetrominoKind>,
+}
+
+impl Fingerprint {
+    fn new(tetris: &Tetris) -> Self {
+        Self {
+            board: tetris.board,
+            kind: tetris.tetro.kind,
+            rotation: tetris.tetro.rotation,
+            x: tetris.tetro.anchor.x,
+            hold: tetris.held(),
+        }
+    }
+}
+
+impl Autoplay {
+    pub fn new(bot: Box<dyn Bot>) -> Self {
+        Self {
+            name: bot.name(),
+            worker: Worker::spawn(bot),
+            plan: vec![],
+            expected: None,
+            pending: None,
+            next_id: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn bot_name(&self) -> &'static str {
+        self.name
+    }
+
+    /// returns true if something changed; signaling to the renderer that it needs to update
+    pub fn tick(&mut self, tetris: &mut Tetris) -> bool {
+        match self.worker.try_recv() {
+            Ok(Some((id, plan))) => {
+                if let Some((_, fingerprint)) = self.pending.take_if(|x| x.0 == id)
+                    // a stale plan is dropped and asked for again below
+                    && fingerprint == Fingerprint::new(tetris)
+                {
+                    self.plan = plan;
+                    self.plan.reverse();
+                    self.expected = Some(fingerprint);
+                }
+            }
+            Ok(None) => {}
+            // the bot panicked, so the pending request is never going to be answered
+            Err(_) => self.pending = None,
+        }
+
+        // the piece moved, locked or was swapped by something other than the plan
+        if self.expected.as_ref() != Some(&Fingerprint::new(tetris)) {
+            self.plan.clear();
+        }
+
+        // nothing to plan for until the next piece is in play
+        if !tetris.in_play() {
+            return false;
+        }
+        self.timer += 1;
+        if self.timer < tetris.rules().autoplay_speed {
+            return false;
+        }
+
+        match self.plan.pop() {
+            Some(action) => {
+                self.timer = 0;
+                tetris.process_action(action);
+                self.expected = Some(Fingerprint::new(tetris));
+                true
+            }
+            None => {
+                if self.pending.is_none() {
+                    self.pending = Some((self.next_id, Fingerprint::new(tetris)));
+                    self.worker.request(self.next_id, tetris.clone());
+                    self.next_id += 1;
+                }
+                false
+            }
+        }
+    }
+}
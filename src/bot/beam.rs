@@ -0,0 +1,234 @@
+use std::time::{Duration, Instant};
+
+use crate::tetris::{
+    GameView,
+    action::Action,
+    bitboard::BitBoard,
+    tetromino::{Tetromino, tetromino_kind::TetrominoKind},
+};
+
+use super::{Bot, movegen};
+
+const DEPTH: usize = 3;
+const WIDTH: usize = 32;
+pub const BUDGET: Duration = Duration::from_millis(50);
+
+/// Looks ahead through the next queue, with and without holding, keeping only the `width` best
+/// boards after each piece. Stops early once `budget` runs out and goes with the best it has, so
+/// only a bot without one plays the same on every machine.
+#[derive(Debug, Clone, Copy)]
+pub struct BeamBot {
+    /// how many pieces to look ahead, counting the current one
+    pub depth: usize,
+    /// how many boards are kept after each piece
+    pub width: usize,
+    /// how long it can think for, as long as the search takes if None
+    pub budget: Option<Duration>,
+}
+
+impl Default for BeamBot {
+    fn default() -> Self {
+        Self {
+            depth: DEPTH,
+            width: WIDTH,
+            budget: Some(BUDGET),
+        }
+    }
+}
+
+impl BeamBot {
+    pub fn new(depth: usize, width: usize, budget: Option<Duration>) -> Self {
+        Self {
+            depth,
+            width,
+            budget,
+        }
+    }
+}
+
+/// a game state somewhere down the search
+#[derive(Debug, Clone)]
+struct Node {
+    board: BitBoard,
+    /// None once the queue has run out
+    current: Option<TetrominoKind>,
+    hold: Option<TetrominoKind>,
+    /// index into the queue of the next piece to come
+    next: usize,
+    lines: u32,
+    grade: f32,
+    /// the inputs for the real current piece that lead here
+    plan: Vec<Action>,
+}
+
+impl Bot for BeamBot {
+    fn name(&self) -> &'static str {
+        "beam"
+    }
+
+    fn plan(&mut self, view: &GameView) -> Vec<Action> {
+        let start = Instant::now();
+        let out_of_time = || self.budget.is_some_and(|budget| start.elapsed() >= budget);
+
+        let root = Node {
+            board: *view.board.bits(),
+            current: Some(view.piece.kind),
+            hold: view.hold,
+            next: 0,
+            lines: 0,
+            grade: f32::MIN,
+            plan: vec![],
+        };
+        let mut beam = expand(&root, view, Some(view.piece), view.can_hold);
+        beam.truncate(self.width);
+
+        for _ in 1..self.depth {
+            if out_of_time() {
+                break;
+            }
+            let mut children = vec![];
+            for node in &beam {
+                children.extend(expand(node, view, None, true));
+                if out_of_time() {
+                    break;
+                }
+            }
+            if children.is_empty() {
+                break;
+            }
+            // each parent's children are sorted, but not all of them together
+            children.sort_by(|a, b| b.grade.total_cmp(&a.grade));
+            children.truncate(self.width);
+            beam = children;
+        }
+
+        beam.into_iter()
+            .next()
+            .map(|node| node.plan)
+            .unwrap_or(vec![Action::HardDrop])
+    }
+}
+
+/// Every node one piece further down, best first. `piece` is where the current piece actually is
+/// when it isn't fresh from spawn.
+fn expand(node: &Node, view: &GameView, piece: Option<Tetromino>, can_hold: bool) -> Vec<Node> {
+    let queue = &view.queue;
+    let mut children = vec![];
+
+    let Some(current) = node.current else {
+        return children;
+    };
+
+    // play the current piece
+    let tetro = piece.unwrap_or(Tetromino::from_kind(current));
+    place(
+        node,
+        view,
+        &tetro,
+        queue.get(node.next).copied(),
+        node.hold,
+        node.next + 1,
+        false,
+        &mut children,
+    );
+
+    // or hold it and play whatever comes out
+    if can_hold {
+        let (kind, next) = match node.hold {
+            Some(kind) => (Some(kind), node.next),
+            None => (queue.get(node.next).copied(), node.next + 1),
+        };
+        if let Some(kind) = kind {
+            let tetro = Tetromino::from_kind(kind);
+            place(
+                node,
+                view,
+                &tetro,
+                queue.get(next).copied(),
+                Some(current),
+                next + 1,
+                true,
+                &mut children,
+            );
+        }
+    }
+
+    children.sort_by(|a, b| b.grade.total_cmp(&a.grade));
+    children
+}
+
+/// adds a child for every placement of the tetromino
+#[allow(clippy::too_many_arguments)]
+fn place(
+    node: &Node,
+    view: &GameView,
+    tetro: &Tetromino,
+    current: Option<TetrominoKind>,
+    hold: Option<TetrominoKind>,
+    next: usize,
+    held: bool,
+    children: &mut Vec<Node>,
+) {
+    for placement in movegen::placements(&node.board, tetro, view.kicks_180) {
+        let mut board = node.board;
+        let lines = node.lines + board.lock(&placement.piece);
+
+        // only the first piece's inputs get played
+        let plan = if node.plan.is_empty() {
+            let mut plan = if held { vec![Action::Hold] } else { vec![] };
+            plan.extend(placement.path);
+            plan
+        } else {
+            node.plan.clone()
+        };
+
+        children.push(Node {
+            grade: view.board.evaluate(&board, lines as f32),
+            board,
+            current,
+            hold,
+            next,
+            lines,
+            plan,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetris::diagram::Diagram;
+
+    #[test]
+    fn plans_without_a_queue() {
+        let diagram: Diagram = "\
+piece: I
+#########.
+#########.
+#########.
+#########.
+"
+        .parse()
+        .unwrap();
+        let plan = BeamBot::default().plan(&diagram.view().unwrap());
+        // standing the I up in the well, not dropping it flat where it spawned
+        assert!(plan.contains(&Action::Rotate(90)) || plan.contains(&Action::Rotate(-90)));
+        assert_eq!(plan.last(), Some(&Action::HardDrop));
+    }
+
+    #[test]
+    fn looks_past_the_current_piece() {
+        let diagram: Diagram = "\
+piece: T
+queue: JO
+........#.
+.#########
+"
+        .parse()
+        .unwrap();
+        let view = diagram.view().unwrap();
+        let greedy = BeamBot::new(1, WIDTH, None).plan(&view);
+        let deep = BeamBot::new(DEPTH, WIDTH, None).plan(&view);
+        assert_ne!(greedy, deep);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::tetris::{Tetris, event::Event};
+
+use super::Bot;
+
+/// How a game played by `play` went.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Outcome {
+    pub pieces: usize,
+    pub lines: u32,
+    pub score: u32,
+    /// how many times four lines were cleared at once
+    pub tetrises: u32,
+    pub perfect_clears: u32,
+    /// the highest the stack got
+    pub max_height: u8,
+    /// false if the game was stopped by the piece cap
+    pub topped_out: bool,
+}
+
+/// Plays the game with the bot as fast as it can think, until it tops out or `max_pieces` have
+/// been placed.
+pub fn play(bot: &mut dyn Bot, tetris: &mut Tetris, max_pieces: Option<usize>) -> Outcome {
+    let mut outcome = Outcome::default();
+
+    while max_pieces.is_none_or(|max| outcome.pieces < max) {
+        for action in bot.plan(&tetris.view()) {
+            // a top out is picked up from the events below
+            if tetris.process_action(action).is_some() {
+                break;
+            }
+        }
+        outcome.pieces += 1;
+        // skips the rules' delays, nobody's watching
+        while !tetris.in_play() {
+            tetris.tick(false);
+        }
+
+        for event in tetris.drain_events() {
+            match event {
+                Event::Lock {
+                    lines,
+                    perfect_clear,
+                    ..
+                } => {
+                    outcome.tetrises += (lines == 4) as u32;
+                    outcome.perfect_clears += perfect_clear as u32;
+                }
+                // the game is reset by now, so the final numbers only come with the event
+                Event::GameOver { lines, score } => {
+                    outcome.lines = lines;
+                    outcome.score = score;
+                    outcome.topped_out = true;
+                }
+            }
+        }
+        if outcome.topped_out {
+            return outcome;
+        }
+        outcome.lines = tetris.lines();
+        outcome.score = tetris.score();
+        // measured once the lines are cleared
+        outcome.max_height = outcome.max_height.max(tetris.board.height());
+    }
+
+    outcome
+}
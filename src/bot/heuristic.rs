@@ -0,0 +1,118 @@
+use crate::tetris::{GameView, action::Action, board::Board, tetromino::Tetromino};
+
+use super::Bot;
+
+/// The original autoplay: tries every rotation, sweeps it across the board and also tries
+/// sliding it left and right after a soft drop, keeping whatever `Board::grade` likes best.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeuristicBot;
+
+impl Bot for HeuristicBot {
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+
+    fn plan(&mut self, view: &GameView) -> Vec<Action> {
+        let board = view.board;
+        let mut final_vec = vec![];
+
+        let mut max = f32::MIN;
+
+        for rotation in 0..4 {
+            // hard dropping
+            let new = rotated(board, &view.piece, rotation);
+
+            for x in [-1.0, 1.0] {
+                let mut moves = rotation_actions(rotation);
+                let mut new_new = new;
+                new_new.anchor.x -= x;
+                while move_x(board, &mut new_new, x) {
+                    let grade = grade_drop(board, &new_new);
+
+                    if grade > max {
+                        let mut new_vec = moves.clone();
+                        new_vec.push(Action::HardDrop);
+                        max = grade;
+                        final_vec = new_vec;
+                    }
+
+                    moves.push(Action::Move(x as i32));
+                }
+            }
+        }
+        for rotation in 0..4 {
+            let new = rotated(board, &view.piece, rotation);
+
+            for x in [-1.0, 1.0] {
+                let mut moves = rotation_actions(rotation);
+                let mut new_new = new;
+                new_new.anchor.x -= x;
+                while move_x(board, &mut new_new, x) {
+                    for x in [1.0, -1.0] {
+                        let mut curr = new_new;
+                        curr.anchor.y += board.drop_distance(&curr);
+
+                        let mut actions = moves.clone();
+                        actions.push(Action::SoftDrop);
+                        while move_x(board, &mut curr, x) {
+                            actions.push(Action::Move(x as i32));
+
+                            let grade = grade_drop(board, &curr);
+
+                            if grade > max {
+                                actions.push(Action::HardDrop);
+                                max = grade;
+                                final_vec = actions.clone();
+                                actions.pop();
+                            }
+                        }
+                    }
+                    moves.push(Action::Move(x as i32));
+                }
+            }
+        }
+
+        final_vec
+    }
+}
+
+/// The inputs for `rotation` quarter turns clockwise. A half turn is played as two quarter ones
+/// so the plan doesn't depend on `Rules::kicks_180`, which the bot can't see.
+fn rotation_actions(rotation: i32) -> Vec<Action> {
+    match rotation {
+        2 => vec![Action::Rotate(90); 2],
+        _ => vec![Action::Rotate(rotation * 90)],
+    }
+}
+
+/// the piece after `rotation_actions`, each turn staying put if it doesn't fit
+fn rotated(board: &Board, tetro: &Tetromino, rotation: i32) -> Tetromino {
+    rotation_actions(rotation)
+        .iter()
+        .fold(*tetro, |tetro, action| match action {
+            Action::Rotate(degrees) => board
+                .rotate(&tetro, (*degrees as f32).to_radians())
+                .unwrap_or(tetro),
+            _ => tetro,
+        })
+}
+
+/// moves the piece sideways if it fits, returns whether it did
+fn move_x(board: &Board, tetro: &mut Tetromino, x: f32) -> bool {
+    tetro.anchor.x += x;
+    let is_valid = board.is_valid(tetro);
+    if !is_valid {
+        tetro.anchor.x -= x;
+    }
+    is_valid
+}
+
+/// hard drops the piece and grades the board it leaves behind
+fn grade_drop(board: &Board, tetro: &Tetromino) -> f32 {
+    let mut bits = *board.bits();
+    let mut tetro = *tetro;
+    tetro.anchor.y += bits.drop_distance(&tetro);
+    let lines = bits.lock(&tetro);
+
+    board.evaluate(&bits, lines as f32)
+}
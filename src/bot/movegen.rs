@@ -0,0 +1,228 @@
+use crate::tetris::{
+    action::Action, bitboard::BitBoard, point::Point, rules::Kicks180, tetromino::Tetromino,
+};
+
+/// the inputs explored from every position
+const MOVES: [Action; 6] = [
+    Action::Move(-1),
+    Action::Move(1),
+    Action::Rotate(90),
+    Action::Rotate(-90),
+    Action::Rotate(180),
+    Action::SoftDrop,
+];
+
+/// A spot the piece can lock in, along with the inputs that get it there.
+#[derive(Debug, Clone)]
+pub struct Placement {
+    /// the piece where it locks
+    pub piece: Tetromino,
+    /// the inputs from the spawn position, ending with `Action::HardDrop`
+    pub path: Vec<Action>,
+}
+
+/// Where a piece is and how it's turned packed into an index, two positions with the same key
+/// behave the same. Anchors sit on half cells, so doubled and shifted they fit in 6 bits each.
+fn key(tetro: &Tetromino) -> usize {
+    let x = ((tetro.anchor.x * 2.).round() as i32 + 16) as usize & 63;
+    let y = ((tetro.anchor.y * 2.).round() as i32 + 16) as usize & 63;
+    (x << 8) | (y << 2) | tetro.rotation as usize
+}
+
+/// one bit for every key
+struct Visited([u64; (1 << 14) / 64]);
+
+impl Visited {
+    /// returns false if the key was already in
+    fn insert(&mut self, key: usize) -> bool {
+        let bit = 1 << (key % 64);
+        let new = self.0[key / 64] & bit == 0;
+        self.0[key / 64] |= bit;
+        new
+    }
+}
+
+/// the cells a locked piece covers, sorted so different rotations landing the same are equal
+fn cells(tetro: &Tetromino) -> [Point<isize>; 4] {
+    let mut cells = tetro.cells();
+    cells.sort_by_key(|point| (point.y, point.x));
+    cells
+}
+
+/// Applies an input to the piece the same way `Tetris::process_action` does, with half turns
+/// kicked by `kicks_180` from the rules, returning None if it can't be played.
+pub fn apply(
+    board: &BitBoard,
+    tetro: &Tetromino,
+    action: Action,
+    kicks_180: Kicks180,
+) -> Option<Tetromino> {
+    match action {
+        Action::Move(x) => {
+            let mut new = *tetro;
+            new.anchor.x += x as f32;
+            board.is_valid(&new).then_some(new)
+        }
+        Action::Rotate(degrees) if degrees.rem_euclid(360) == 180 => {
+            board.rotate_180(tetro, kicks_180)
+        }
+        Action::Rotate(degrees) => board.rotate(tetro, (degrees as f32).to_radians()),
+        Action::SoftDrop | Action::HardDrop => {
+            let mut new = *tetro;
+            new.anchor.y += board.drop_distance(tetro);
+            Some(new)
+        }
+        _ => None,
+    }
+}
+
+/// Breadth first search over every position the piece can reach with the real movement and
+/// rotation rules. Returns each distinct place it can lock in once, with the shortest input path
+/// to it, so spins and tucks under overhangs are found too.
+pub fn placements(board: &BitBoard, piece: &Tetromino, kicks_180: Kicks180) -> Vec<Placement> {
+    // every reached position with the index of the one it came from and the input in between
+    // it's also the queue, everything past `index` is still to be expanded
+    let mut nodes: Vec<(Tetromino, usize, Action)> = vec![(*piece, 0, Action::None)];
+    let mut visited = Visited([0; (1 << 14) / 64]);
+    visited.insert(key(piece));
+
+    // there's only a few dozen placements, a list beats hashing
+    let mut locked = vec![];
+    let mut placements = vec![];
+
+    let mut index = 0;
+    while index < nodes.len() {
+        let tetro = nodes[index].0;
+
+        let mut landed = tetro;
+        landed.anchor.y += board.drop_distance(&tetro);
+        let landed_cells = cells(&landed);
+        if !locked.contains(&landed_cells) {
+            locked.push(landed_cells);
+            let mut path = vec![Action::HardDrop];
+            let mut node = index;
+            while node != 0 {
+                path.push(nodes[node].2);
+                node = nodes[node].1;
+            }
+            path.reverse();
+            placements.push(Placement {
+                piece: landed,
+                path,
+            });
+        }
+
+        for action in MOVES {
+            let Some(new) = apply(board, &tetro, action, kicks_180) else {
+                continue;
+            };
+            if visited.insert(key(&new)) {
+                nodes.push((new, index, action));
+            }
+        }
+        index += 1;
+    }
+
+    placements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetris::{
+        Tetris, diagram::Diagram, rules::Rules, tetromino::tetromino_kind::TetrominoKind,
+    };
+
+    /// an overhang over the T slot, so a T only gets in by turning at the bottom
+    const TSD: &str = "\
+piece: T
+###.......
+#...######
+##.#######
+";
+
+    #[test]
+    fn finds_the_t_spin_double() {
+        let diagram: Diagram = TSD.parse().unwrap();
+        let piece = Tetromino::from_kind(diagram.piece.unwrap());
+        let board = diagram.board.bits();
+        let slot = [(1, 18), (2, 18), (3, 18), (2, 19)].map(|(x, y)| Point::new(x, y));
+
+        let placement = placements(board, &piece, Kicks180::default())
+            .into_iter()
+            .find(|x| cells(&x.piece) == slot)
+            .expect("the slot is reachable");
+        assert!(
+            placement
+                .path
+                .iter()
+                .any(|x| matches!(x, Action::Rotate(_)))
+        );
+
+        // the path plays out to the same spot
+        let mut tetro = piece;
+        for &action in &placement.path {
+            tetro = apply(board, &tetro, action, Kicks180::default()).unwrap();
+        }
+        assert_eq!(cells(&tetro), cells(&placement.piece));
+    }
+
+    #[test]
+    fn every_placement_is_distinct() {
+        let diagram: Diagram = TSD.parse().unwrap();
+        let piece = Tetromino::from_kind(diagram.piece.unwrap());
+        let placements = placements(diagram.board.bits(), &piece, Kicks180::default());
+        for (i, a) in placements.iter().enumerate() {
+            assert_eq!(a.path.last(), Some(&Action::HardDrop));
+            assert!(
+                placements[i + 1..]
+                    .iter()
+                    .all(|b| cells(&a.piece) != cells(&b.piece))
+            );
+        }
+    }
+
+    #[test]
+    fn plays_like_the_game() {
+        let diagram: Diagram = TSD.parse().unwrap();
+        for kicks_180 in [Kicks180::Off, Kicks180::Nudge, Kicks180::SrsPlus] {
+            for action in MOVES {
+                let mut tetris =
+                    Tetris::with_pieces(diagram.board, &[diagram.piece.unwrap()], None);
+                tetris.set_rules(Rules {
+                    kicks_180,
+                    ..Rules::default()
+                });
+                // down by the slot, where kicks have something to push against
+                tetris.process_action(Action::SoftDrop);
+                let tetro = tetris.tetro;
+                tetris.process_action(action);
+
+                let played = cells(&tetris.tetro);
+                let applied =
+                    apply(tetris.board.bits(), &tetro, action, kicks_180).map(|x| cells(&x));
+                assert_eq!(
+                    applied.unwrap_or(cells(&tetro)),
+                    played,
+                    "{:?} {:?}",
+                    kicks_180,
+                    action
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn half_turns_only_with_the_rules() {
+        let piece = Tetromino::from_kind(TetrominoKind::T);
+        let board = BitBoard::default();
+        let uses_180 = |kicks_180| {
+            placements(&board, &piece, kicks_180)
+                .iter()
+                .any(|x| x.path.contains(&Action::Rotate(180)))
+        };
+        assert!(!uses_180(Kicks180::Off));
+        // flipping the T over is one input instead of two
+        assert!(uses_180(Kicks180::SrsPlus));
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::HashSet;
+
+use crate::tetris::{
+    GameView,
+    action::Action,
+    bitboard::{BitBoard, HEIGHT, WIDTH},
+    rules::Kicks180,
+    tetromino::{Tetromino, tetromino_kind::TetrominoKind},
+};
+
+use super::{
+    Bot,
+    beam::BeamBot,
+    movegen::{self, Placement},
+};
+
+/// lines the bot tries to perfect clear within
+const PC_LINES: usize = 4;
+/// positions looked at before the solver gives up
+const MAX_NODES: usize = 5_000;
+
+/// One piece of a perfect clear.
+#[derive(Debug, Clone)]
+pub struct Step {
+    /// whether the piece comes out of hold, so `Action::Hold` has to be played first
+    pub hold: bool,
+    pub placement: Placement,
+}
+
+impl Step {
+    /// the inputs that play the step
+    pub fn actions(&self) -> Vec<Action> {
+        let mut actions = if self.hold {
+            vec![Action::Hold]
+        } else {
+            vec![]
+        };
+        actions.extend(&self.placement.path);
+        actions
+    }
+}
+
+/// The pieces still to come in a search: the one in play, hold and the rest of the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Pieces {
+    current: Option<TetrominoKind>,
+    hold: Option<TetrominoKind>,
+    /// index into the queue of the next piece
+    next: usize,
+}
+
+struct Solver<'a> {
+    queue: &'a [TetrominoKind],
+    kicks_180: Kicks180,
+    /// positions already known to lead nowhere
+    dead: HashSet<(BitBoard, Pieces, usize)>,
+    nodes: usize,
+}
+
+/// Looks for placements that leave the board empty without the stack ever going above
+/// `max_lines`, using the piece in play, hold and the queue. `piece` is the piece in play where
+/// it currently is, and half turns kick the way `kicks_180` says. The lowest clears are tried
+/// first. Returns the steps in order, or None if there's no perfect clear with these pieces (or it
+/// took too long to find one).
+pub fn solve(
+    board: &BitBoard,
+    piece: &Tetromino,
+    hold: Option<TetrominoKind>,
+    can_hold: bool,
+    queue: &[TetrominoKind],
+    kicks_180: Kicks180,
+    max_lines: usize,
+) -> Option<Vec<Step>> {
+    let mut solver = Solver {
+        queue,
+        kicks_180,
+        dead: HashSet::new(),
+        nodes: 0,
+    };
+    let pieces = Pieces {
+        current: Some(piece.kind),
+        hold,
+        next: 0,
+    };
+    // every height that leaves room for whole pieces, each with its own node budget
+    let lowest = (board.height() as usize).max(1);
+    (lowest..=max_lines.min(HEIGHT)).find_map(|lines| {
+        solver.nodes = 0;
+        let mut steps = solver.search(board, pieces, Some(piece), can_hold, lines)?;
+        steps.reverse();
+        Some(steps)
+    })
+}
+
+impl Solver<'_> {
+    /// returns the steps in reverse
+    fn search(
+        &mut self,
+        board: &BitBoard,
+        pieces: Pieces,
+        piece: Option<&Tetromino>,
+        can_hold: bool,
+        lines: usize,
+    ) -> Option<Vec<Step>> {
+        self.nodes += 1;
+        if self.nodes > MAX_NODES || !self.possible(board, &pieces, lines) {
+            return None;
+        }
+        if self.dead.contains(&(*board, pieces, lines)) {
+            return None;
+        }
+
+        // play the current piece
+        if let Some(current) = pieces.current {
+            let tetro = piece.copied().unwrap_or(Tetromino::from_kind(current));
+            let after = Pieces {
+                current: self.queue.get(pieces.next).copied(),
+                hold: pieces.hold,
+                next: pieces.next + 1,
+            };
+            if let Some(steps) = self.try_piece(board, &tetro, after, false, lines) {
+                return Some(steps);
+            }
+        }
+
+        // or swap it with hold
+        if can_hold && pieces.current.is_some() {
+            let (kind, next) = match pieces.hold {
+                Some(kind) => (Some(kind), pieces.next),
+                None => (self.queue.get(pieces.next).copied(), pieces.next + 1),
+            };
+            if let Some(kind) = kind {
+                let after = Pieces {
+                    current: self.queue.get(next).copied(),
+                    hold: pieces.current,
+                    next: next + 1,
+                };
+                let tetro = Tetromino::from_kind(kind);
+                if let Some(steps) = self.try_piece(board, &tetro, after, true, lines) {
+                    return Some(steps);
+                }
+            }
+        }
+
+        // running out of nodes doesn't mean there's nothing here
+        if self.nodes <= MAX_NODES {
+            self.dead.insert((*board, pieces, lines));
+        }
+        None
+    }
+
+    fn try_piece(
+        &mut self,
+        board: &BitBoard,
+        tetro: &Tetromino,
+        after: Pieces,
+        hold: bool,
+        lines: usize,
+    ) -> Option<Vec<Step>> {
+        for placement in movegen::placements(board, tetro, self.kicks_180) {
+            // the piece has to stay inside the bottom `lines` rows
+            if placement
+                .piece
+                .cells()
+                .iter()
+                .any(|point| point.y < (HEIGHT - lines) as isize)
+            {
+                continue;
+            }
+
+            let mut board = *board;
+            let cleared = board.lock(&placement.piece) as usize;
+            let step = Step { hold, placement };
+            if board.is_empty() {
+                return Some(vec![step]);
+            }
+
+            if let Some(mut steps) = self.search(&board, after, None, true, lines - cleared) {
+                steps.push(step);
+                return Some(steps);
+            }
+        }
+        None
+    }
+
+    /// quick checks that there are exactly enough empty cells for the pieces to fill
+    fn possible(&self, board: &BitBoard, pieces: &Pieces, lines: usize) -> bool {
+        if lines == 0 || board.height() as usize > lines {
+            return false;
+        }
+        let filled: u32 = board.rows[HEIGHT - lines..]
+            .iter()
+            .map(|row| row.count_ones())
+            .sum();
+        let empty = (lines * WIDTH) as u32 - filled;
+
+        let available = pieces.current.is_some() as usize
+            + pieces.hold.is_some() as usize
+            + self.queue.len().saturating_sub(pieces.next);
+        empty.is_multiple_of(4) && (empty / 4) as usize <= available
+    }
+}
+
+/// Goes for a perfect clear whenever the solver finds one within the next queue and plays like
+/// the beam search bot otherwise.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PcBot {
+    pub fallback: BeamBot,
+}
+
+impl Bot for PcBot {
+    fn name(&self) -> &'static str {
+        "pc"
+    }
+
+    fn plan(&mut self, view: &GameView) -> Vec<Action> {
+        let solution = solve(
+            view.board.bits(),
+            &view.piece,
+            view.hold,
+            view.can_hold,
+            &view.queue,
+            view.kicks_180,
+            PC_LINES,
+        );
+        match solution.as_deref() {
+            Some([first, ..]) => first.actions(),
+            _ => self.fallback.plan(view),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetris::diagram::Diagram;
+
+    /// solves the diagram and checks the steps really leave the board empty
+    fn solve_diagram(diagram: &str, max_lines: usize) -> Option<Vec<Step>> {
+        let diagram: Diagram = diagram.parse().unwrap();
+        let piece = Tetromino::from_kind(diagram.piece.unwrap());
+        let steps = solve(
+            diagram.board.bits(),
+            &piece,
+            diagram.hold,
+            true,
+            &diagram.queue,
+            Kicks180::default(),
+            max_lines,
+        )?;
+
+        let mut board = *diagram.board.bits();
+        for step in &steps {
+            board.lock(&step.placement.piece);
+            assert!(board.height() as usize <= max_lines);
+        }
+        assert!(board.is_empty());
+        Some(steps)
+    }
+
+    const TWO_LINES: &str = "\
+piece: I
+queue: O
+......####
+####..####
+";
+
+    #[test]
+    fn two_line_clear() {
+        assert_eq!(solve_diagram(TWO_LINES, 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn two_line_clear_within_more_lines() {
+        assert_eq!(solve_diagram(TWO_LINES, 4).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn two_line_clear_from_empty_board() {
+        let diagram = "piece: I\nqueue: OOOJLSZT";
+        assert_eq!(solve_diagram(diagram, 2).unwrap().len(), 5);
+        assert_eq!(solve_diagram(diagram, 4).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn four_line_clear() {
+        let diagram = "\
+piece: I
+#########.
+#########.
+#########.
+#########.
+";
+        assert_eq!(solve_diagram(diagram, 4).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn four_line_clear_with_squares() {
+        let diagram = "\
+piece: O
+queue: OOO
+....######
+....######
+....######
+....######
+";
+        assert!(solve_diagram(diagram, 4).is_some());
+        assert!(solve_diagram(diagram, 3).is_none());
+    }
+
+    #[test]
+    fn no_clear_without_the_pieces() {
+        assert!(solve_diagram("piece: I\nqueue: O", 4).is_none());
+    }
+}
@@ -0,0 +1,51 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context, bail};
+
+use crate::tetris::board::features::{FEATURE_NAMES, FEATURES};
+
+/// where the trainer writes its best weights and the game looks for them
+pub const WEIGHTS_PATH: &str = "tetrust_weights.json";
+
+/// reads evaluator weights written by `save`, a json object from feature name to weight. Every
+/// feature has to be there, a file from before one was added has to be trained again
+pub fn load(path: impl AsRef<Path>) -> anyhow::Result<[f32; FEATURES]> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let mut map: BTreeMap<String, f32> = serde_json::from_str(&text)?;
+
+    let mut weights = [0.; FEATURES];
+    for (weight, name) in weights.iter_mut().zip(FEATURE_NAMES) {
+        *weight = map
+            .remove(name)
+            .with_context(|| format!("missing feature {name} in {}", path.display()))?;
+    }
+    if let Some(name) = map.keys().next() {
+        bail!("unknown feature {name} in {}", path.display());
+    }
+    Ok(weights)
+}
+
+pub fn save(path: impl AsRef<Path>, weights: &[f32; FEATURES]) -> anyhow::Result<()> {
+    let map: BTreeMap<&str, f32> = FEATURE_NAMES.into_iter().zip(*weights).collect();
+    fs::write(path, serde_json::to_string_pretty(&map)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_feature_has_to_be_there() {
+        let path = std::env::temp_dir().join("tetrust_missing_weights.json");
+        let weights = std::array::from_fn(|i| i as f32);
+        save(&path, &weights).unwrap();
+        assert_eq!(load(&path).unwrap(), weights);
+
+        fs::write(&path, format!("{{\"{}\": 1.0}}", FEATURE_NAMES[0])).unwrap();
+        let error = load(&path).unwrap_err().to_string();
+        assert!(error.contains(FEATURE_NAMES[1]));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    sync::mpsc::{self, Receiver, RecvError, Sender, TryRecvError},
+    thread,
+};
+
+use crate::tetris::{Tetris, action::Action};
+
+use super::Bot;
+
+/// A bot thinking on its own thread, so a slow search doesn't hold up the game loop. Requests
+/// carry a snapshot of the game and an id that comes back with the plan.
+pub struct Worker {
+    requests: Sender<(u64, Tetris)>,
+    results: Receiver<(u64, Vec<Action>)>,
+}
+
+impl Worker {
+    pub fn spawn(mut bot: Box<dyn Bot>) -> Self {
+        let (requests, receiver) = mpsc::channel::<(u64, Tetris)>();
+        let (sender, results) = mpsc::channel();
+
+        thread::spawn(move || {
+            // ends once the worker is dropped and the channel closes
+            while let Ok(request) = receiver.recv() {
+                // requests that piled up while thinking are already stale, only answer the newest
+                let (id, tetris) = receiver.try_iter().last().unwrap_or(request);
+                if sender.send((id, bot.plan(&tetris.view()))).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { requests, results }
+    }
+
+    pub fn request(&self, id: u64, tetris: Tetris) {
+        // the thread only goes away if the bot panicked, `try_recv` reports that
+        let _ = self.requests.send((id, tetris));
+    }
+
+    /// The newest finished plan, if any. Errors once the thread is gone and no more plans are
+    /// coming.
+    pub fn try_recv(&self) -> Result<Option<(u64, Vec<Action>)>, RecvError> {
+        let mut newest = None;
+        loop {
+            match self.results.try_recv() {
+                Ok(result) => newest = Some(result),
+                Err(TryRecvError::Empty) => return Ok(newest),
+                Err(TryRecvError::Disconnected) => return newest.map(Some).ok_or(RecvError),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::tetris::GameView;
+
+    struct Panics;
+
+    impl Bot for Panics {
+        fn name(&self) -> &'static str {
+            "panics"
+        }
+
+        fn plan(&mut self, _: &GameView) -> Vec<Action> {
+            panic!("no plan");
+        }
+    }
+
+    #[test]
+    fn reports_a_panicked_bot() {
+        let worker = Worker::spawn(Box::new(Panics));
+        worker.request(0, Tetris::with_seed(0));
+        let start = Instant::now();
+        while worker.try_recv() == Ok(None) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::yield_now();
+        }
+        assert_eq!(worker.try_recv(), Err(RecvError));
+    }
+}
@@ -1,9 +1,19 @@
+//! The game engine in [`tetris`] has no graphics dependencies and can be driven headless. The
+//! window, renderer and input handling are behind the `render` feature, which is on by default.
+
+pub mod bot;
+#[cfg(feature = "render")]
 mod state;
+pub mod tetris;
+#[cfg(feature = "render")]
 mod vertex;
 
+#[cfg(feature = "render")]
 use std::sync::Arc;
 
+#[cfg(feature = "render")]
 use state::State;
+#[cfg(feature = "render")]
 use winit::{
     application::ApplicationHandler,
     event::*,
@@ -12,17 +22,40 @@ use winit::{
     window::Window,
 };
 
+#[cfg(feature = "render")]
 #[derive(Default)]
 pub struct App {
     state: Option<State>,
+    /// a fumen to start practicing from instead of the saved game
+    fumen: Option<String>,
+    /// a puzzle pack to play instead of the saved game
+    puzzles: Option<String>,
 }
 
+#[cfg(feature = "render")]
 impl App {
     pub fn new() -> Self {
-        Self { state: None }
+        Self::default()
+    }
+
+    /// starts from the first page of the fumen instead of the saved game
+    pub fn with_fumen(fumen: String) -> Self {
+        Self {
+            fumen: Some(fumen),
+            ..Self::default()
+        }
+    }
+
+    /// plays the puzzles in the file instead of the saved game
+    pub fn with_puzzles(path: String) -> Self {
+        Self {
+            puzzles: Some(path),
+            ..Self::default()
+        }
     }
 }
 
+#[cfg(feature = "render")]
 impl ApplicationHandler<State> for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         #[allow(unused_mut)]
@@ -32,7 +65,18 @@ impl ApplicationHandler<State> for App {
 
         // If we are not on web we can use pollster to
         // await the
-        self.state = Some(pollster::block_on(State::new(window)).unwrap());
+        let mut state = pollster::block_on(State::new(window)).unwrap();
+        if let Some(fumen) = &self.fumen
+            && let Err(e) = state.load_fumen(fumen)
+        {
+            log::error!("Unable to load fumen {}", e);
+        }
+        if let Some(path) = &self.puzzles
+            && let Err(e) = state.load_puzzles(path)
+        {
+            log::error!("Unable to load puzzles {}", e);
+        }
+        self.state = Some(state);
     }
 
     #[allow(unused_mut)]
@@ -52,12 +96,14 @@ impl ApplicationHandler<State> for App {
         };
 
         match event {
-            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::CloseRequested => state.quit(event_loop),
             WindowEvent::Resized(size) => {
                 state.resize(size.width, size.height);
                 state.new_vertices()
             }
             WindowEvent::RedrawRequested => {
+                #[cfg(feature = "gamepad")]
+                state.poll_gamepads(event_loop);
                 state.update();
                 match state.render() {
                     Ok(_) => {}
@@ -72,16 +118,22 @@ impl ApplicationHandler<State> for App {
                     }
                 }
             }
-            WindowEvent::MouseInput { state, button, .. } => match (button, state.is_pressed()) {
-                (MouseButton::Left, true) => {}
-                (MouseButton::Left, false) => {}
-                _ => {}
-            },
+            WindowEvent::Touch(touch) => state.handle_touch(event_loop, touch),
+            WindowEvent::CursorMoved { position, .. } => state.handle_cursor(Some(position)),
+            WindowEvent::CursorLeft { .. } => state.handle_cursor(None),
+            WindowEvent::MouseWheel { delta, .. } => state.handle_wheel(delta),
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } => state.handle_click(button),
             WindowEvent::KeyboardInput {
                 event:
                     KeyEvent {
                         physical_key: PhysicalKey::Code(code),
                         state: key_state,
+                        // held keys are handled by the game, see `tetris::handling`
+                        repeat: false,
                         ..
                     },
                 ..
@@ -91,10 +143,17 @@ impl ApplicationHandler<State> for App {
     }
 }
 
+#[cfg(feature = "render")]
 pub fn run() -> anyhow::Result<()> {
     env_logger::init();
     let event_loop = EventLoop::with_user_event().build()?;
-    let mut app = App::new();
+    // `--fumen <data>` practices a shared setup, `--puzzles <file>` plays a puzzle pack
+    let mut args = std::env::args().skip(1);
+    let mut app = match (args.next().as_deref(), args.next()) {
+        (Some("--fumen"), Some(fumen)) => App::with_fumen(fumen),
+        (Some("--puzzles"), Some(path)) => App::with_puzzles(path),
+        _ => App::new(),
+    };
     event_loop.run_app(&mut app)?;
 
     Ok(())
@@ -1,10 +1,61 @@
-mod tetris;
+mod font;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod keymap;
+mod layout;
+mod mouse;
+mod palette;
+mod screen;
+mod touch;
 
 use super::vertex::Vertex;
-use std::{cmp::Ordering, iter, sync::Arc};
-use tetris::{Tetris, action::Action};
+use crate::bot::{self, Autoplay};
+use crate::tetris::{
+    Tetris,
+    action::Action,
+    clock::Clock,
+    event::Event,
+    fumen,
+    handling::Handling,
+    puzzle::{Pack, Status},
+    rules::{RULES_PATH, Rules},
+    tetromino::Tetromino,
+};
+#[cfg(feature = "gamepad")]
+use gamepad::{GAMEPADS_PATH, Gamepads};
+use keymap::{Command, KEYS_PATH, Keymap};
+use layout::{BUTTONS, Layout, Rect};
+use mouse::MousePlacement;
+use palette::Palette;
+use screen::{COUNTDOWN, Item, MenuInput, Mode, Results, SPRINT_LINES, Screen};
+use std::{collections::HashSet, fs, iter, sync::Arc};
+use touch::TouchControls;
 use wgpu::util::DeviceExt;
-use winit::{event_loop::ActiveEventLoop, keyboard::KeyCode, window::Window};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{MouseButton, MouseScrollDelta, Touch},
+    event_loop::ActiveEventLoop,
+    keyboard::KeyCode,
+    window::Window,
+};
+
+/// where the game in progress is written on quit and read back on the next launch
+const SAVE_PATH: &str = "tetrust_save.json";
+/// the puzzles played from the mode select unless another pack is given on the command line
+const PUZZLES_PATH: &str = "puzzles/basics.txt";
+/// the most quads for menu text, lines past it are cut off
+const TEXT_QUADS: usize = 4096;
+/// the most quads drawn at once, the board's cells, the touch buttons and the menus
+const MAX_QUADS: usize = 10 * 20 + BUTTONS + TEXT_QUADS;
+
+/// what a command came in on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Source {
+    Keyboard,
+    #[cfg(feature = "gamepad")]
+    Gamepad,
+    Touch,
+}
 
 pub struct State {
     surface: wgpu::Surface<'static>,
@@ -19,8 +70,32 @@ pub struct State {
     pub window: Arc<Window>,
 
     soft: bool,
-    pause: bool,
+    screen: Screen,
+    /// index into `screen.items()` of the highlighted menu entry
+    selected: usize,
+    mode: Mode,
+    /// ticks the current game has been played for
+    played: u32,
+    puzzles_path: String,
+    /// the rules as the settings screen was opened, so only what changed there is saved
+    rules_before_settings: Rules,
+    handling: Handling,
+    /// the directions held down and on what, so letting go on one source doesn't stop the same
+    /// direction held on another
+    held_directions: HashSet<(Source, i32)>,
+    clock: Clock,
     tetris: Tetris,
+    palette: Palette,
+    keymap: Keymap,
+    #[cfg(feature = "gamepad")]
+    gamepads: Option<Gamepads>,
+    touch: TouchControls,
+    mouse: MousePlacement,
+    /// index into `bot::BOTS` of the bot autoplay uses
+    bot: usize,
+    autoplay: Option<Autoplay>,
+    /// the puzzles being played, if any
+    puzzles: Option<Pack>,
 }
 
 impl State {
@@ -145,11 +220,11 @@ impl State {
             cache: None,
         });
 
-        let vertices = [Vertex::default(); 10 * 20 * 4];
-        let mut indices = [0u16; 10 * 20 * 6 + 1];
+        let vertices = [Vertex::default(); MAX_QUADS * 4];
+        let mut indices = [0u16; MAX_QUADS * 6 + 1];
         let mut vi = 0;
         let mut ii = 0;
-        for _ in 0..(20 * 10) {
+        for _ in 0..MAX_QUADS {
             let v = vi as u16;
 
             // i literaly have no clue why the fuck its like this this is genuinely mind fick
@@ -200,9 +275,28 @@ impl State {
             num_indices,
             window,
 
-            pause: false,
             soft: false,
-            tetris: Tetris::new(),
+            screen: Screen::Title,
+            selected: 0,
+            mode: Mode::Marathon,
+            played: 0,
+            puzzles_path: PUZZLES_PATH.into(),
+            rules_before_settings: Rules::default(),
+            handling: Handling::default(),
+            held_directions: HashSet::new(),
+            clock: Clock::new(),
+            tetris: load_game(),
+            palette: Palette::default(),
+            keymap: load_keymap(),
+            #[cfg(feature = "gamepad")]
+            gamepads: Gamepads::new(GAMEPADS_PATH)
+                .inspect_err(|e| log::error!("Unable to start controllers {:#}", e))
+                .ok(),
+            touch: TouchControls::default(),
+            mouse: MousePlacement::default(),
+            bot: 0,
+            autoplay: None,
+            puzzles: None,
         };
 
         state.resize(size.width, size.height);
@@ -220,40 +314,487 @@ impl State {
     }
 
     pub fn update(&mut self) {
-        if !self.pause && self.tetris.update(self.soft) {
+        // the clock keeps running in the menus so leaving them doesn't replay the time spent
+        let ticks = self.clock.ticks();
+        match self.screen {
+            Screen::Countdown(left) => {
+                let left = left.saturating_sub(ticks);
+                self.screen = match left {
+                    0 => Screen::Playing,
+                    left => Screen::Countdown(left),
+                };
+                self.new_vertices();
+                return;
+            }
+            Screen::Playing => {}
+            _ => return,
+        }
+
+        let mut changed = false;
+        for _ in 0..ticks {
+            self.played += 1;
+            if let Some(autoplay) = &mut self.autoplay {
+                changed |= autoplay.tick(&mut self.tetris);
+            }
+            for action in self.handling.tick(self.tetris.rules()) {
+                self.tetris.process_action(action);
+                changed = true;
+            }
+            changed |= self.tetris.tick(self.soft);
+        }
+        self.handle_events();
+        if self.mode == Mode::Sprint
+            && self.screen == Screen::Playing
+            && self.tetris.lines() >= SPRINT_LINES
+        {
+            self.finish_game(true, self.tetris.lines(), self.tetris.score());
+        }
+        if changed {
             self.new_vertices();
         }
     }
 
-    #[rustfmt::skip]
+    fn handle_events(&mut self) {
+        for event in self.tetris.drain_events() {
+            if let Some(pack) = &mut self.puzzles {
+                match pack.update(event) {
+                    Status::Solved => {
+                        log::info!("Solved {}", pack.current().name);
+                        let tetris = pack.next_puzzle();
+                        self.start(tetris);
+                        self.log_puzzle();
+                        return;
+                    }
+                    Status::Failed => {
+                        log::info!("Failed {}, try again", pack.current().name);
+                        let tetris = pack.restart();
+                        self.start(tetris);
+                        return;
+                    }
+                    Status::Playing => {}
+                }
+            }
+
+            match event {
+                Event::Lock {
+                    perfect_clear: true,
+                    ..
+                } => log::info!("Perfect clear!"),
+                Event::GameOver { lines, score } => {
+                    log::info!("Game over with {} lines", lines);
+                    if self.mode != Mode::Puzzles {
+                        self.finish_game(false, lines, score);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
     pub fn handle_key(&mut self, event_loop: &ActiveEventLoop, key: KeyCode, pressed: bool) {
+        // releases still go to the game so nothing stays held after a menu
+        if self.screen != Screen::Playing && pressed {
+            let input = MenuInput::from_key(key)
+                .or_else(|| self.keymap.get(key).and_then(MenuInput::from_command));
+            if let Some(input) = input {
+                self.handle_menu(event_loop, input);
+            }
+            return;
+        }
+        if let Some(command) = self.keymap.get(key) {
+            self.handle_command(event_loop, command, pressed, Source::Keyboard);
+        }
+    }
+
+    /// plays the commands from controllers, the same way as keys
+    #[cfg(feature = "gamepad")]
+    pub fn poll_gamepads(&mut self, event_loop: &ActiveEventLoop) {
+        let commands = match &mut self.gamepads {
+            Some(gamepads) => gamepads.poll(),
+            None => return,
+        };
+        for (command, pressed) in commands {
+            self.handle_command(event_loop, command, pressed, Source::Gamepad);
+        }
+    }
+
+    /// plays the commands from touch buttons and gestures, the same way as keys
+    pub fn handle_touch(&mut self, event_loop: &ActiveEventLoop, touch: Touch) {
+        let layout = self.layout();
+        for (command, pressed) in self.touch.handle(&touch, &layout) {
+            self.handle_command(event_loop, command, pressed, Source::Touch);
+        }
+        // the buttons show up with the first touch and light up while held
+        self.new_vertices();
+    }
+
+    /// moves the preview in mouse mode
+    pub fn handle_cursor(&mut self, position: Option<PhysicalPosition<f64>>) {
+        match position {
+            Some(position) => self.mouse.move_cursor(position),
+            None => self.mouse.leave(),
+        }
+        if self.mouse.enabled {
+            self.new_vertices();
+        }
+    }
+
+    /// turns the preview in mouse mode
+    pub fn handle_wheel(&mut self, delta: MouseScrollDelta) {
+        if self.mouse.enabled && self.mouse.scroll(delta) {
+            self.new_vertices();
+        }
+    }
+
+    /// In mouse mode a left click plays the inputs to the previewed placement and a right click
+    /// holds. Clicks where the piece can't get to do nothing.
+    pub fn handle_click(&mut self, button: MouseButton) {
+        if !self.mouse.enabled || self.screen != Screen::Playing || self.autoplay.is_some() {
+            return;
+        }
+        let path = match button {
+            MouseButton::Left => match self.mouse.placement(&self.tetris, &self.layout()) {
+                Some(placement) => placement.path,
+                None => return,
+            },
+            MouseButton::Right => vec![Action::Hold],
+            _ => return,
+        };
+        for action in path {
+            self.tetris.process_action(action);
+        }
+        self.mouse.reset_rotation();
+        self.handle_events();
+        self.new_vertices();
+    }
+
+    fn toggle_mouse(&mut self) {
+        self.mouse.enabled = !self.mouse.enabled;
+        self.mouse.reset_rotation();
+        log::info!(
+            "Mouse mode {}",
+            if self.mouse.enabled { "on" } else { "off" }
+        );
+    }
+
+    #[rustfmt::skip]
+    fn handle_command(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        command: Command,
+        pressed: bool,
+        source: Source,
+    ) {
+        if self.screen != Screen::Playing && pressed {
+            if let Some(input) = MenuInput::from_command(command) {
+                self.handle_menu(event_loop, input);
+            }
+            return;
+        }
         let mut done = true;
         let mut action = Action::None;
-        #[allow(clippy::single_match)]
-        match (key, pressed) {
-            (KeyCode::Space, true) => action = Action::HardDrop,
-            (KeyCode::ArrowLeft, true) => action = Action::Move(-1),
-            (KeyCode::ArrowRight, true) => action = Action::Move(1),
-            (KeyCode::ArrowUp, true) => action = Action::Rotate(90),
-            (KeyCode::ArrowDown, true) => action = Action::Rotate(-90),
-            (KeyCode::KeyH, true) => action = Action::Hold,
-            (KeyCode::KeyS, true) => action = Action::SoftDrop,
-
-            (KeyCode::KeyP, true) => self.pause = !self.pause,
-            (KeyCode::KeyA, true) => self.tetris.toggle_autoplay(),
-            (KeyCode::KeyR, true) => self.tetris.reset(),
-
-            (KeyCode::Escape, true) => event_loop.exit(),
-            (KeyCode::ShiftLeft, true) => self.soft = true,
-            (KeyCode::ShiftLeft, false) => self.soft = false,
+        if let Some(input) = spawn_input(command) {
+            self.tetris.set_input_held(input, pressed);
+        }
+        match (command, pressed) {
+            (Command::HardDrop, true) => action = Action::HardDrop,
+            (Command::Left, pressed) => action = self.hold_direction(source, -1, pressed),
+            (Command::Right, pressed) => action = self.hold_direction(source, 1, pressed),
+            (Command::RotateCw, true) => action = Action::Rotate(90),
+            (Command::RotateCcw, true) => action = Action::Rotate(-90),
+            (Command::Rotate180, true) => action = Action::Rotate(180),
+            (Command::Hold, true) => action = Action::Hold,
+            (Command::SonicDrop, true) => action = Action::SoftDrop,
+
+            (Command::Pause, true) => self.show(Screen::Paused),
+            (Command::Autoplay, true) => self.toggle_autoplay(),
+            (Command::NextBot, true) => self.next_bot(),
+            (Command::Restart, true) => self.restart(),
+            (Command::NextPuzzle, true) => self.step_puzzle(Pack::next_puzzle),
+            (Command::PreviousPuzzle, true) => self.step_puzzle(Pack::previous_puzzle),
+            (Command::ExportFumen, true) => self.export_fumen(),
+            (Command::MouseMode, true) => self.toggle_mouse(),
+
+            (Command::Quit, true) => self.quit(event_loop),
+            (Command::SoftDrop, pressed) => self.soft = pressed,
             _ => done = false,
         }
         if done {
             self.tetris.process_action(action);
+            self.handle_events();
             self.new_vertices();
         }
     }
 
+    /// presses or lets go of a direction on one source, the handling only hears about the first
+    /// press and the last release across all of them
+    fn hold_direction(&mut self, source: Source, direction: i32, pressed: bool) -> Action {
+        let held = |held: &HashSet<(Source, i32)>| held.iter().any(|&(_, x)| x == direction);
+        let before = held(&self.held_directions);
+        if pressed {
+            self.held_directions.insert((source, direction));
+        } else {
+            self.held_directions.remove(&(source, direction));
+        }
+        match (before, held(&self.held_directions)) {
+            (false, true) => self.handling.press(direction),
+            (true, false) => {
+                self.handling.release(direction);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn handle_menu(&mut self, event_loop: &ActiveEventLoop, input: MenuInput) {
+        let items = self.screen.items();
+        let selected = items.get(self.selected).copied();
+        match (input, selected) {
+            (MenuInput::Up, _) if !items.is_empty() => {
+                self.selected = (self.selected + items.len() - 1) % items.len();
+            }
+            (MenuInput::Down, _) if !items.is_empty() => {
+                self.selected = (self.selected + 1) % items.len();
+            }
+            (MenuInput::Left, Some(Item::Setting(setting))) => self.adjust(setting, -1),
+            (MenuInput::Right, Some(Item::Setting(setting))) => self.adjust(setting, 1),
+            (MenuInput::Confirm, Some(item)) => self.choose(event_loop, item),
+            (MenuInput::Back, _) => self.back(),
+            _ => {}
+        }
+        self.new_vertices();
+    }
+
+    fn choose(&mut self, event_loop: &ActiveEventLoop, item: Item) {
+        match item {
+            Item::Start => self.show(Screen::ModeSelect),
+            Item::Settings => {
+                self.rules_before_settings = *self.tetris.rules();
+                self.show(Screen::Settings {
+                    paused: self.screen == Screen::Paused,
+                });
+            }
+            Item::Quit => self.quit(event_loop),
+            Item::Mode(mode) => self.start_mode(mode),
+            Item::Resume => self.show(Screen::Countdown(COUNTDOWN)),
+            Item::Restart => {
+                self.restart();
+                self.show(Screen::Countdown(COUNTDOWN));
+            }
+            Item::Retry => self.start_mode(self.mode),
+            Item::Title => self.show_title(),
+            Item::Setting(setting) => self.adjust(setting, 1),
+            Item::Back => self.back(),
+        }
+    }
+
+    fn back(&mut self) {
+        match self.screen {
+            Screen::Title | Screen::Playing => {}
+            Screen::ModeSelect => self.show(Screen::Title),
+            Screen::Countdown(_) => self.show(Screen::Paused),
+            Screen::Paused => self.show(Screen::Countdown(COUNTDOWN)),
+            Screen::Results(_) => self.show_title(),
+            Screen::Settings { paused } => {
+                let rules = *self.tetris.rules();
+                if rules != self.rules_before_settings
+                    && let Err(e) = rules.save_changes(&self.rules_before_settings, RULES_PATH)
+                {
+                    log::error!("Unable to save rules {:#}", e);
+                }
+                self.show(if paused {
+                    Screen::Paused
+                } else {
+                    Screen::Title
+                });
+            }
+        }
+    }
+
+    fn show(&mut self, screen: Screen) {
+        self.screen = screen;
+        self.selected = 0;
+    }
+
+    fn adjust(&mut self, setting: screen::Setting, step: i32) {
+        let mut rules = *self.tetris.rules();
+        setting.adjust(&mut rules, step);
+        self.tetris.set_rules(rules);
+    }
+
+    /// Starts a game in the mode after the countdown. A marathon picks up where the last one was
+    /// left, and is saved before another mode takes over.
+    fn start_mode(&mut self, mode: Mode) {
+        if self.mode == Mode::Marathon
+            && let Err(e) = self.save_game()
+        {
+            log::error!("Unable to save game {}", e);
+        }
+        match mode {
+            Mode::Marathon => {
+                self.puzzles = None;
+                self.tetris = load_game();
+            }
+            Mode::Sprint => {
+                self.puzzles = None;
+                self.start(Tetris::with_rules(*self.tetris.rules()));
+            }
+            Mode::Puzzles => {
+                let path = self.puzzles_path.clone();
+                if let Err(e) = self.load_puzzles(&path) {
+                    log::error!("Unable to load puzzles {}", e);
+                    return;
+                }
+            }
+        }
+        self.mode = mode;
+        self.played = 0;
+        self.show(Screen::Countdown(COUNTDOWN));
+    }
+
+    /// goes back to the title screen, saving a marathon in progress
+    fn show_title(&mut self) {
+        if self.mode == Mode::Marathon
+            && let Err(e) = self.save_game()
+        {
+            log::error!("Unable to save game {}", e);
+        }
+        self.show(Screen::Title);
+    }
+
+    /// shows how the game went, a topped out game has already started over by now
+    fn finish_game(&mut self, cleared: bool, lines: u32, score: u32) {
+        self.show(Screen::Results(Results {
+            mode: self.mode,
+            cleared,
+            lines,
+            score,
+            ticks: self.played,
+        }));
+        self.played = 0;
+        self.new_vertices();
+    }
+
+    fn toggle_autoplay(&mut self) {
+        self.autoplay = match self.autoplay {
+            Some(_) => None,
+            None => bot::by_name(bot::BOTS[self.bot]).map(Autoplay::new),
+        };
+    }
+
+    /// switches to the next bot, taking over from the current one if autoplay is on
+    fn next_bot(&mut self) {
+        self.bot = (self.bot + 1) % bot::BOTS.len();
+        log::info!("Selected bot {}", bot::BOTS[self.bot]);
+        if self.autoplay.is_some() {
+            self.autoplay = bot::by_name(bot::BOTS[self.bot]).map(Autoplay::new);
+        }
+    }
+
+    /// starts a new game, or the current puzzle over
+    fn restart(&mut self) {
+        match &mut self.puzzles {
+            Some(pack) => {
+                let tetris = pack.restart();
+                self.start(tetris);
+            }
+            None => self.tetris.reset(),
+        }
+        self.played = 0;
+    }
+
+    /// switches to a puzzle game, keeping the rules and the evaluator weights
+    fn start(&mut self, mut tetris: Tetris) {
+        tetris.board.set_weights(self.tetris.board.weights());
+        tetris.set_rules(*self.tetris.rules());
+        self.tetris = tetris;
+        self.new_vertices();
+    }
+
+    /// plays the puzzles in the file one after the other, the game in progress isn't saved
+    /// while they're on
+    pub fn load_puzzles(&mut self, path: &str) -> anyhow::Result<()> {
+        let mut pack = Pack::load(path)?;
+        let tetris = pack.restart();
+        self.puzzles = Some(pack);
+        self.puzzles_path = path.into();
+        self.mode = Mode::Puzzles;
+        self.start(tetris);
+        self.log_puzzle();
+        self.show(Screen::Countdown(COUNTDOWN));
+        Ok(())
+    }
+
+    fn step_puzzle(&mut self, step: fn(&mut Pack) -> Tetris) {
+        if let Some(pack) = &mut self.puzzles {
+            let tetris = step(pack);
+            self.start(tetris);
+            self.log_puzzle();
+        }
+    }
+
+    fn log_puzzle(&self) {
+        if let Some(pack) = &self.puzzles {
+            let (index, len) = pack.progress();
+            let puzzle = pack.current();
+            log::info!(
+                "Puzzle {}/{} {}: {:?}",
+                index,
+                len,
+                puzzle.name,
+                puzzle.goal
+            );
+        }
+    }
+
+    /// Puts the first page of a fumen on the board to practice from. The page's piece, if it has
+    /// one, becomes the piece in play. Any later pages are left out.
+    pub fn load_fumen(&mut self, data: &str) -> anyhow::Result<()> {
+        let pages = fumen::decode(data)?;
+        if pages.len() > 1 {
+            log::warn!("Only the first of {} fumen pages is loaded", pages.len());
+        }
+        let page = pages
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("fumen has no pages"))?;
+        if let Some(piece) = page.piece {
+            self.tetris.tetro = Tetromino::from_kind(piece.kind);
+        }
+        self.tetris.load_board(page.board);
+        if !page.comment.is_empty() {
+            log::info!("{}", page.comment);
+        }
+        self.show(Screen::Countdown(COUNTDOWN));
+        self.new_vertices();
+        Ok(())
+    }
+
+    /// logs the board and the piece in play as a fumen so it can be shared
+    fn export_fumen(&self) {
+        let page = fumen::Page {
+            board: self.tetris.board,
+            piece: Some(self.tetris.tetro),
+            ..Default::default()
+        };
+        log::info!("Fumen {}", fumen::encode(&[page]));
+    }
+
+    /// saves the game in progress and exits
+    pub fn quit(&self, event_loop: &ActiveEventLoop) {
+        if self.mode == Mode::Marathon
+            && let Err(e) = self.save_game()
+        {
+            log::error!("Unable to save game {}", e);
+        }
+        event_loop.exit();
+    }
+
+    fn save_game(&self) -> anyhow::Result<()> {
+        fs::write(SAVE_PATH, serde_json::to_string(&self.tetris)?)?;
+        Ok(())
+    }
+
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
         self.window.request_redraw();
 
@@ -310,65 +851,46 @@ impl State {
         if !self.is_surface_configured {
             return;
         }
-        let board = self.tetris.get_full_board();
-        let mut vertices = [Vertex::default(); 10 * 20 * 4];
-        let ratio = self.config.width as f32 / self.config.height as f32;
-        let (width, height, startx, starty) = match ratio.total_cmp(&0.5) {
-            Ordering::Equal => (1. / 5., 1. / 10., -1., -1.),
-            Ordering::Less => {
-                let width = 1. / 5.;
-                let startx = -1.0;
-                let height = width * ratio;
-                let starty = -(10. * height);
-
-                (width, height, startx, starty)
-            }
-            Ordering::Greater => {
-                let height = 1. / 10.;
-                let starty = -1.0;
-                let width = height / ratio;
-                let startx = -(5. * width);
-
-                (width, height, startx, starty)
+        let layout = self.layout();
+        let board = if self.mouse.enabled {
+            // nowhere to go shows no ghost rather than the one straight down
+            match self.mouse.placement(&self.tetris, &layout) {
+                Some(placement) => self.tetris.board_with_ghost(&placement.piece),
+                None => self.tetris.board_with_ghost(&self.tetris.tetro),
             }
+        } else {
+            self.tetris.get_full_board()
         };
+        let (width, height) = layout.cell;
 
-        let color = [0.01; 3];
-        let mut vi = 0;
-        for (y, row) in board.iter().rev().enumerate() {
-            for (x, cell) in row.iter().enumerate() {
-                let color = if let tetris::cell::Cell::Filled(c) = *cell {
-                    c
+        let mut vertices = Vec::with_capacity(MAX_QUADS * 4);
+        if self.screen.shows_board() {
+            for (y, row) in board.iter().rev().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    let rect = Rect {
+                        x: layout.board.x + width * x as f32,
+                        y: layout.board.y + height * y as f32,
+                        width,
+                        height,
+                    };
+                    push_quad(&mut vertices, rect, self.palette.color(*cell));
+                }
+            }
+        }
+        if self.touch.enabled && self.screen == Screen::Playing {
+            for (rect, command) in layout.buttons() {
+                let color = if self.touch.is_pressed(command) {
+                    self.palette.button_pressed
                 } else {
-                    color
-                };
-                let fx = startx + width * x as f32;
-                let fy = starty + height * y as f32;
-
-                vertices[vi] = Vertex {
-                    // top left
-                    position: [fx, fy, 0.0],
-                    color,
-                };
-                vertices[vi + 1] = Vertex {
-                    // top right
-                    position: [fx + width, fy, 0.0],
-                    color,
-                };
-                vertices[vi + 2] = Vertex {
-                    // bottom left
-                    position: [fx, fy + height, 0.0],
-                    color,
+                    self.palette.button
                 };
-                vertices[vi + 3] = Vertex {
-                    // bottom right
-                    position: [fx + width, fy + height, 0.0],
-                    color,
-                };
-
-                vi += 4;
+                push_quad(&mut vertices, rect, color);
             }
         }
+        self.push_menu(&mut vertices, &layout);
+        vertices.truncate(MAX_QUADS * 4);
+
+        self.num_indices = (vertices.len() / 4 * 6) as u32;
         self.vertex_buffer = self
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -377,4 +899,160 @@ impl State {
                 usage: wgpu::BufferUsages::VERTEX,
             });
     }
+
+    /// The screen's text in the middle of the board, on a backdrop if the board is showing.
+    /// Font pixels are a sixth of a cell, or smaller when a line wouldn't fit the window.
+    fn push_menu(&self, vertices: &mut Vec<Vertex>, layout: &Layout) {
+        let mut lines = self.screen.lines(self.tetris.rules(), self.selected);
+        if lines.is_empty() {
+            return;
+        }
+        // the window is 2 wide, the font gets smaller if the longest line doesn't fit and big
+        // lines come down a size until they do
+        let longest = lines.iter().map(|line| font::width(&line.text)).max();
+        let shrink = (1.9 / (longest.unwrap_or(0) as f32 * layout.cell.0 / 6.)).min(1.);
+        let pixel = (layout.cell.0 / 6. * shrink, layout.cell.1 / 6. * shrink);
+        let fits = (1.9 / pixel.0) as usize;
+        for line in &mut lines {
+            while line.scale > 1 && font::width(&line.text) * line.scale > fits {
+                line.scale -= 1;
+            }
+        }
+        // a blank pixel under each glyph row, two between lines
+        let line_height = |line: &screen::Line| (font::GLYPH_HEIGHT + 2) * line.scale;
+        let height = lines.iter().map(line_height).sum::<usize>() as f32 * pixel.1;
+        let width = lines
+            .iter()
+            .map(|line| font::width(&line.text) * line.scale)
+            .max()
+            .unwrap_or(0) as f32
+            * pixel.0;
+        let centre_x = layout.board.x + layout.board.width / 2.;
+        let mut top = layout.board.y + layout.board.height / 2. + height / 2.;
+
+        if self.screen.shows_board() {
+            let margin = (pixel.0 * 4., pixel.1 * 4.);
+            let rect = Rect {
+                x: centre_x - width / 2. - margin.0,
+                y: top - height - margin.1,
+                width: width + margin.0 * 2.,
+                height: height + margin.1 * 2.,
+            };
+            push_quad(vertices, rect, self.palette.backdrop);
+        }
+        for line in &lines {
+            let scale = line.scale as f32;
+            let size = (pixel.0 * scale, pixel.1 * scale);
+            let left = centre_x - font::width(&line.text) as f32 * size.0 / 2.;
+            let color = if line.selected {
+                self.palette.text_selected
+            } else {
+                self.palette.text
+            };
+            for (x, y) in font::pixels(&line.text) {
+                let rect = Rect {
+                    x: left + x as f32 * size.0,
+                    y: top - (y + 1) as f32 * size.1,
+                    width: size.0,
+                    height: size.1,
+                };
+                push_quad(vertices, rect, color);
+            }
+            top -= line_height(line) as f32 * pixel.1;
+        }
+    }
+
+    fn layout(&self) -> Layout {
+        Layout::new(self.config.width, self.config.height)
+    }
+}
+
+/// the input a command plays on a piece as it spawns while held down, see `Tetris::set_input_held`
+fn spawn_input(command: Command) -> Option<Action> {
+    match command {
+        Command::RotateCw => Some(Action::Rotate(90)),
+        Command::RotateCcw => Some(Action::Rotate(-90)),
+        Command::Rotate180 => Some(Action::Rotate(180)),
+        Command::Hold => Some(Action::Hold),
+        _ => None,
+    }
+}
+
+/// adds the four corners of a rectangle, in the order the index buffer expects
+fn push_quad(vertices: &mut Vec<Vertex>, rect: Rect, color: [f32; 3]) {
+    let Rect {
+        x,
+        y,
+        width,
+        height,
+    } = rect;
+    vertices.extend([
+        // top left
+        Vertex {
+            position: [x, y, 0.0],
+            color,
+        },
+        // top right
+        Vertex {
+            position: [x + width, y, 0.0],
+            color,
+        },
+        // bottom left
+        Vertex {
+            position: [x, y + height, 0.0],
+            color,
+        },
+        // bottom right
+        Vertex {
+            position: [x + width, y + height, 0.0],
+            color,
+        },
+    ]);
+}
+
+/// resumes the saved game if there is one, otherwise starts a new one. the rules file and
+/// trained weights are plugged in when there are any
+fn load_game() -> Tetris {
+    let rules = load_rules();
+    let mut tetris = match fs::read_to_string(SAVE_PATH) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Unable to load saved game {}", e);
+            Tetris::with_rules(rules)
+        }),
+        Err(_) => Tetris::with_rules(rules),
+    };
+    tetris.set_rules(rules);
+
+    if fs::exists(bot::weights::WEIGHTS_PATH).unwrap_or(false) {
+        match bot::weights::load(bot::weights::WEIGHTS_PATH) {
+            Ok(weights) => tetris.board.set_weights(weights),
+            Err(e) => log::warn!("Unable to load weights {}", e),
+        }
+    }
+    tetris
+}
+
+/// the rules from the rules file, or the default ones if there's no file or it's invalid
+fn load_rules() -> Rules {
+    if !fs::exists(RULES_PATH).unwrap_or(false) {
+        return Rules::default();
+    }
+    Rules::load(RULES_PATH).unwrap_or_else(|e| {
+        log::error!("Unable to load rules {:#}, playing by the default ones", e);
+        Rules::default()
+    })
+}
+
+/// the key bindings from the bindings file, or the default ones if there's no file or it's invalid
+fn load_keymap() -> Keymap {
+    if !fs::exists(KEYS_PATH).unwrap_or(false) {
+        return Keymap::default();
+    }
+    Keymap::load(KEYS_PATH).unwrap_or_else(|e| {
+        log::error!(
+            "Unable to load key bindings {:#}, using the default ones",
+            e
+        );
+        Keymap::default()
+    })
 }
@@ -0,0 +1,189 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::Context;
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+use serde::Deserialize;
+
+use super::keymap::{Command, Keymap};
+
+/// where the controller bindings are read from on launch
+pub const GAMEPADS_PATH: &str = "tetrust_gamepads.toml";
+
+/// how far a stick has to be pushed to count as pressing that way
+const STICK_THRESHOLD: f32 = 0.5;
+
+#[rustfmt::skip]
+const DEFAULT_BUTTONS: &[(Command, &[Button])] = &[
+    (Command::Left, &[Button::DPadLeft]),
+    (Command::Right, &[Button::DPadRight]),
+    (Command::SoftDrop, &[Button::DPadDown]),
+    (Command::HardDrop, &[Button::DPadUp]),
+    (Command::RotateCw, &[Button::South]),
+    (Command::RotateCcw, &[Button::East]),
+    (Command::Rotate180, &[Button::North]),
+    (Command::Hold, &[Button::West, Button::LeftTrigger, Button::RightTrigger]),
+    (Command::Pause, &[Button::Start]),
+    (Command::Restart, &[Button::Select]),
+];
+
+/// The controller bindings file. `default` changes the bindings of every controller and each
+/// table under `controllers` those of the controllers with that name, on top of `default`:
+///
+/// ```toml
+/// [default]
+/// hard_drop = ["DPadUp", "RightTrigger2"]
+///
+/// [controllers."Xbox Wireless Controller"]
+/// rotate_cw = ["East"]
+/// rotate_ccw = ["South"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    #[serde(default)]
+    default: HashMap<Command, Vec<Button>>,
+    #[serde(default)]
+    controllers: HashMap<String, HashMap<Command, Vec<Button>>>,
+}
+
+/// Controllers, read through gilrs. Buttons are bound to the same `Command`s as keys and the left
+/// stick moves and soft drops like the d-pad; gilrs already turns d-pads reported as axes into
+/// button presses. Controllers can come and go while the game runs.
+pub struct Gamepads {
+    gilrs: Gilrs,
+    default: Keymap<Button>,
+    /// bindings for controllers by name
+    controllers: HashMap<String, Keymap<Button>>,
+    /// which way each controller's stick is pushed, sideways and whether it's down
+    sticks: HashMap<GamepadId, (i32, bool)>,
+    /// commands held on each controller, let go of if it's unplugged
+    held: HashMap<GamepadId, Vec<Command>>,
+}
+
+impl Gamepads {
+    pub fn new(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file: File = if fs::exists(path)? {
+            toml::from_str(&fs::read_to_string(path)?)
+                .with_context(|| format!("in {}", path.display()))?
+        } else {
+            File::default()
+        };
+
+        let default = Keymap::with_bindings(file.default.clone(), DEFAULT_BUTTONS)?;
+        let mut controllers = HashMap::new();
+        for (name, bindings) in file.controllers {
+            let mut merged = file.default.clone();
+            merged.extend(bindings);
+            let keymap = Keymap::with_bindings(merged, DEFAULT_BUTTONS)
+                .with_context(|| format!("for {}", name))?;
+            controllers.insert(name, keymap);
+        }
+
+        let gilrs = Gilrs::new().map_err(|e| anyhow::anyhow!("{}", e))?;
+        for (_, gamepad) in gilrs.gamepads() {
+            log::info!("Controller {} connected", gamepad.name());
+        }
+
+        Ok(Self {
+            gilrs,
+            default,
+            controllers,
+            sticks: HashMap::new(),
+            held: HashMap::new(),
+        })
+    }
+
+    /// Takes the controller events since the last call, returning the commands pressed and
+    /// released in order.
+    pub fn poll(&mut self) -> Vec<(Command, bool)> {
+        let mut commands = vec![];
+        while let Some(event) = self.gilrs.next_event() {
+            let id = event.id;
+            match event.event {
+                EventType::Connected => {
+                    log::info!("Controller {} connected", self.gilrs.gamepad(id).name());
+                }
+                EventType::Disconnected => {
+                    log::info!("Controller {} disconnected", self.gilrs.gamepad(id).name());
+                    self.sticks.remove(&id);
+                    for command in self.held.remove(&id).unwrap_or_default() {
+                        commands.push((command, false));
+                    }
+                }
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(command) = self.keymap(id).get(button) {
+                        self.press(id, command, true, &mut commands);
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(command) = self.keymap(id).get(button) {
+                        self.press(id, command, false, &mut commands);
+                    }
+                }
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => {
+                    let (x, down) = self.sticks.get(&id).copied().unwrap_or_default();
+                    let new = if value.abs() < STICK_THRESHOLD {
+                        0
+                    } else {
+                        value.signum() as i32
+                    };
+                    if new != x {
+                        self.sticks.insert(id, (new, down));
+                        self.push_stick(id, x, false, &mut commands);
+                        self.push_stick(id, new, true, &mut commands);
+                    }
+                }
+                EventType::AxisChanged(Axis::LeftStickY, value, _) => {
+                    let (x, down) = self.sticks.get(&id).copied().unwrap_or_default();
+                    // up is positive
+                    let new = value < -STICK_THRESHOLD;
+                    if new != down {
+                        self.sticks.insert(id, (x, new));
+                        self.press(id, Command::SoftDrop, new, &mut commands);
+                    }
+                }
+                _ => {}
+            }
+        }
+        commands
+    }
+
+    fn keymap(&self, id: GamepadId) -> &Keymap<Button> {
+        let gamepad = self.gilrs.gamepad(id);
+        self.controllers
+            .get(gamepad.name())
+            .unwrap_or(&self.default)
+    }
+
+    /// the stick pushed towards `direction`, nothing if it's 0
+    fn push_stick(
+        &mut self,
+        id: GamepadId,
+        direction: i32,
+        pressed: bool,
+        commands: &mut Vec<(Command, bool)>,
+    ) {
+        match direction {
+            -1 => self.press(id, Command::Left, pressed, commands),
+            1 => self.press(id, Command::Right, pressed, commands),
+            _ => {}
+        }
+    }
+
+    fn press(
+        &mut self,
+        id: GamepadId,
+        command: Command,
+        pressed: bool,
+        commands: &mut Vec<(Command, bool)>,
+    ) {
+        let held = self.held.entry(id).or_default();
+        if pressed {
+            held.push(command);
+        } else if let Some(index) = held.iter().position(|&x| x == command) {
+            held.remove(index);
+        }
+        commands.push((command, pressed));
+    }
+}
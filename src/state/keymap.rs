@@ -0,0 +1,153 @@
+use std::{collections::HashMap, fmt::Debug, fs, hash::Hash, path::Path};
+
+use anyhow::{Context, bail};
+use serde::Deserialize;
+use winit::keyboard::KeyCode;
+
+/// where the key bindings are read from on launch
+pub const KEYS_PATH: &str = "tetrust_keys.toml";
+
+/// Everything a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    Left,
+    Right,
+    /// falls faster while held, see `Rules::sdf`
+    SoftDrop,
+    /// drops the piece to the bottom without locking it
+    SonicDrop,
+    HardDrop,
+    RotateCw,
+    RotateCcw,
+    #[serde(rename = "rotate_180")]
+    Rotate180,
+    Hold,
+    Pause,
+    Autoplay,
+    NextBot,
+    Restart,
+    NextPuzzle,
+    PreviousPuzzle,
+    ExportFumen,
+    /// places pieces by pointing and clicking, see `state::mouse`
+    MouseMode,
+    Quit,
+}
+
+#[rustfmt::skip]
+const DEFAULT_KEYS: &[(Command, &[KeyCode])] = &[
+    (Command::Left, &[KeyCode::ArrowLeft]),
+    (Command::Right, &[KeyCode::ArrowRight]),
+    (Command::SoftDrop, &[KeyCode::ShiftLeft]),
+    (Command::SonicDrop, &[KeyCode::KeyS]),
+    (Command::HardDrop, &[KeyCode::Space]),
+    (Command::RotateCw, &[KeyCode::ArrowUp]),
+    (Command::RotateCcw, &[KeyCode::ArrowDown]),
+    (Command::Rotate180, &[KeyCode::KeyC]),
+    (Command::Hold, &[KeyCode::KeyH]),
+    (Command::Pause, &[KeyCode::Escape, KeyCode::KeyP]),
+    (Command::Autoplay, &[KeyCode::KeyA]),
+    (Command::NextBot, &[KeyCode::KeyB]),
+    (Command::Restart, &[KeyCode::KeyR]),
+    (Command::NextPuzzle, &[KeyCode::KeyN, KeyCode::PageDown]),
+    (Command::PreviousPuzzle, &[KeyCode::PageUp]),
+    (Command::ExportFumen, &[KeyCode::KeyF]),
+    (Command::MouseMode, &[KeyCode::KeyM]),
+    // quit from the menus, or bind a key to it
+    (Command::Quit, &[]),
+];
+
+/// Which command each key plays, or each button for controllers. The bindings file lists the keys
+/// for the commands it wants to change, by winit's `KeyCode` names:
+///
+/// ```toml
+/// left = ["ArrowLeft", "KeyJ"]
+/// rotate_cw = ["KeyX", "ArrowUp"]
+/// rotate_180 = ["KeyA"]
+/// ```
+///
+/// Commands left out keep their default keys, apart from any the file took for something else.
+#[derive(Debug, Clone)]
+pub struct Keymap<K = KeyCode>(HashMap<K, Command>);
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::with_bindings(HashMap::new(), DEFAULT_KEYS).expect("the default keys don't overlap")
+    }
+}
+
+impl Keymap {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).with_context(|| format!("in {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        Self::with_bindings(toml::from_str(text)?, DEFAULT_KEYS)
+    }
+}
+
+impl<K: Copy + Eq + Hash + Debug> Keymap<K> {
+    /// binds the keys listed for each command, the commands that aren't listed get their keys
+    /// from `defaults` as long as nothing else took them
+    pub fn with_bindings(
+        bindings: HashMap<Command, Vec<K>>,
+        defaults: &[(Command, &[K])],
+    ) -> anyhow::Result<Self> {
+        let mut keys = HashMap::new();
+        for (&command, codes) in &bindings {
+            for &key in codes {
+                if let Some(other) = keys.insert(key, command)
+                    && other != command
+                {
+                    bail!("{:?} is bound to both {:?} and {:?}", key, other, command);
+                }
+            }
+        }
+
+        for (command, codes) in defaults {
+            if !bindings.contains_key(command) {
+                for &key in *codes {
+                    keys.entry(key).or_insert(*command);
+                }
+            }
+        }
+        Ok(Self(keys))
+    }
+
+    pub fn get(&self, key: K) -> Option<Command> {
+        self.0.get(&key).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_key_bound_twice_is_an_error() {
+        assert!(Keymap::parse("left = [\"KeyJ\"]\nright = [\"KeyJ\"]").is_err());
+        // listing it twice for the same command is fine
+        assert!(Keymap::parse("left = [\"KeyJ\", \"KeyJ\"]").is_ok());
+    }
+
+    #[test]
+    fn the_file_takes_keys_from_the_defaults() {
+        let keymap = Keymap::parse("hold = [\"ArrowLeft\"]").unwrap();
+        assert_eq!(keymap.get(KeyCode::ArrowLeft), Some(Command::Hold));
+        // hold's own default is gone, the commands the file left alone keep theirs
+        assert_eq!(keymap.get(KeyCode::KeyH), None);
+        assert_eq!(keymap.get(KeyCode::Space), Some(Command::HardDrop));
+    }
+
+    #[test]
+    fn several_keys_per_command() {
+        let keymap = Keymap::parse("left = [\"ArrowLeft\", \"KeyJ\"]").unwrap();
+        assert_eq!(keymap.get(KeyCode::ArrowLeft), Some(Command::Left));
+        assert_eq!(keymap.get(KeyCode::KeyJ), Some(Command::Left));
+        assert_eq!(keymap.get(KeyCode::Escape), Some(Command::Pause));
+        assert_eq!(keymap.get(KeyCode::KeyP), Some(Command::Pause));
+    }
+}
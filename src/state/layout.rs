@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+
+use winit::dpi::PhysicalPosition;
+
+use super::keymap::Command;
+
+/// how many on-screen buttons there are, see `Layout::buttons`
+pub const BUTTONS: usize = 8;
+
+/// A rectangle in normalized device coordinates, x going right and y going up from -1 to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// Where things go on screen for a window size. The board keeps its cells square and fills the
+/// window's height, or its width if the window is narrower than the board. The space left over
+/// at the sides or the bottom holds the touch buttons.
+#[derive(Debug, Clone, Copy)]
+pub struct Layout {
+    /// the whole board
+    pub board: Rect,
+    /// size of a cell
+    pub cell: (f32, f32),
+    /// the window size in pixels
+    pub size: (f32, f32),
+}
+
+impl Layout {
+    pub fn new(width: u32, height: u32) -> Self {
+        let ratio = width as f32 / height as f32;
+        let (cell_width, cell_height, x, y) = match ratio.total_cmp(&0.5) {
+            Ordering::Equal => (1. / 5., 1. / 10., -1., -1.),
+            Ordering::Less => {
+                let width = 1. / 5.;
+                let height = width * ratio;
+                (width, height, -1.0, -(10. * height))
+            }
+            Ordering::Greater => {
+                let height = 1. / 10.;
+                let width = height / ratio;
+                (width, height, -(5. * width), -1.0)
+            }
+        };
+
+        Self {
+            board: Rect {
+                x,
+                y,
+                width: cell_width * 10.,
+                height: cell_height * 20.,
+            },
+            cell: (cell_width, cell_height),
+            size: (width as f32, height as f32),
+        }
+    }
+
+    /// a cell's width in pixels
+    pub fn cell_pixels(&self) -> f32 {
+        self.cell.0 * self.size.0 / 2.
+    }
+
+    /// a position in the window in normalized device coordinates
+    pub fn ndc(&self, position: PhysicalPosition<f64>) -> (f32, f32) {
+        (
+            position.x as f32 / self.size.0 * 2. - 1.,
+            1. - position.y as f32 / self.size.1 * 2.,
+        )
+    }
+
+    /// The touch buttons. In a wide window they go in a column on each side of the board, the
+    /// moves at the bottom where thumbs rest, and in a tall one in two rows under it.
+    #[rustfmt::skip]
+    pub fn buttons(&self) -> [(Rect, Command); BUTTONS] {
+        const PADDING: f32 = 0.02;
+        let grid = |x: f32, y: f32, width: f32, height: f32| Rect {
+            x: x + PADDING,
+            y: y + PADDING,
+            width: (width - 2. * PADDING).max(0.),
+            height: (height - 2. * PADDING).max(0.),
+        };
+
+        if self.board.y <= -1. {
+            // columns at the sides, from the top
+            let width = self.board.x + 1.;
+            let side = |x: f32, i: usize| grid(x, 1. - (i + 1) as f32 * 0.5, width, 0.5);
+            let right = self.board.x + self.board.width;
+            [
+                (side(-1., 0), Command::Hold),
+                (side(-1., 1), Command::RotateCcw),
+                (side(-1., 2), Command::SoftDrop),
+                (side(-1., 3), Command::Left),
+                (side(right, 0), Command::HardDrop),
+                (side(right, 1), Command::RotateCw),
+                (side(right, 2), Command::Rotate180),
+                (side(right, 3), Command::Right),
+            ]
+        } else {
+            // two rows under the board
+            let height = (self.board.y + 1.) / 2.;
+            let below = |column: usize, row: usize| {
+                grid(-1. + column as f32 * 0.5, self.board.y - (row + 1) as f32 * height, 0.5, height)
+            };
+            [
+                (below(0, 0), Command::Hold),
+                (below(1, 0), Command::RotateCcw),
+                (below(2, 0), Command::RotateCw),
+                (below(3, 0), Command::HardDrop),
+                (below(0, 1), Command::Left),
+                (below(1, 1), Command::SoftDrop),
+                (below(2, 1), Command::Rotate180),
+                (below(3, 1), Command::Right),
+            ]
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use winit::{dpi::PhysicalPosition, event::MouseScrollDelta};
+
+use super::layout::Layout;
+use crate::{
+    bot::movegen::{self, Placement},
+    tetris::{Tetris, point::Point, tetromino::Tetromino},
+};
+
+/// scrolled pixels that make a turn, for touchpads
+const PIXELS_PER_TURN: f64 = 40.;
+
+/// Placing pieces with the mouse. The piece goes where the cursor points, turned by the scroll
+/// wheel, and the ghost shows where it would land. Only the places it can really get to from
+/// where it is are offered, with the inputs to get there, so a click can't cheat it through the
+/// stack.
+#[derive(Debug, Default)]
+pub struct MousePlacement {
+    pub enabled: bool,
+    cursor: Option<PhysicalPosition<f64>>,
+    /// quarter turns clockwise from the spawn orientation
+    rotation: u8,
+    /// scrolling that hasn't added up to a turn yet
+    scroll: f64,
+}
+
+impl MousePlacement {
+    pub fn move_cursor(&mut self, position: PhysicalPosition<f64>) {
+        self.cursor = Some(position);
+    }
+
+    pub fn leave(&mut self) {
+        self.cursor = None;
+    }
+
+    /// turns the piece a quarter for every notch, returns true if it turned
+    pub fn scroll(&mut self, delta: MouseScrollDelta) -> bool {
+        self.scroll += match delta {
+            MouseScrollDelta::LineDelta(_, y) => y as f64,
+            MouseScrollDelta::PixelDelta(position) => position.y / PIXELS_PER_TURN,
+        };
+        let turns = self.scroll.trunc();
+        self.scroll -= turns;
+        // scrolling down turns clockwise
+        self.rotation = (self.rotation as i32 - turns as i32).rem_euclid(4) as u8;
+        turns != 0.
+    }
+
+    /// starts the next piece in its spawn orientation
+    pub fn reset_rotation(&mut self) {
+        self.rotation = 0;
+        self.scroll = 0.;
+    }
+
+    /// The reachable placement closest to the cursor with the chosen orientation, first by
+    /// column then by row so tucks under overhangs can be picked by pointing at them.
+    pub fn placement(&self, tetris: &Tetris, layout: &Layout) -> Option<Placement> {
+        if !tetris.in_play() {
+            return None;
+        }
+        let (x, y) = layout.ndc(self.cursor?);
+        let column = (x - layout.board.x) / layout.cell.0;
+        let row = 20. - (y - layout.board.y) / layout.cell.1;
+
+        let mut wanted = tetris.tetro;
+        let turns = self.rotation as i32 - wanted.rotation as i32;
+        wanted.rotate((turns as f32 * 90.).to_radians());
+        // well inside the board so none of it gets clamped to the top row
+        wanted.anchor.x += 10.;
+        wanted.anchor.y += 10.;
+        let wanted = shape(&wanted);
+
+        movegen::placements(tetris.board.bits(), &tetris.tetro, tetris.rules().kicks_180)
+            .into_iter()
+            .filter(|placement| shape(&placement.piece) == wanted)
+            .min_by(|a, b| distance(a, column, row).total_cmp(&distance(b, column, row)))
+    }
+}
+
+/// the cells relative to the top left one, so the same shape anywhere compares equal
+fn shape(tetro: &Tetromino) -> [Point<isize>; 4] {
+    let mut cells = tetro.cells();
+    cells.sort_by_key(|point| (point.y, point.x));
+    let first = cells[0];
+    cells.map(|point| Point::new(point.x - first.x, point.y - first.y))
+}
+
+/// how far the middle of the placed piece is from the cursor, columns outweighing rows
+fn distance(placement: &Placement, column: f32, row: f32) -> f32 {
+    let cells = placement.piece.cells();
+    let x = cells.iter().map(|point| point.x as f32 + 0.5).sum::<f32>() / 4.;
+    let y = cells.iter().map(|point| point.y as f32 + 0.5).sum::<f32>() / 4.;
+    (x - column).abs() * 100. + (y - row).abs()
+}
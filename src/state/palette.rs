@@ -0,0 +1,62 @@
+use crate::tetris::{cell::Cell, tetromino::tetromino_kind::TetrominoKind};
+
+/// Colours the renderer paints cells with. The game only knows what's in a cell, so a skin is
+/// just a different palette.
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub empty: [f32; 3],
+    /// indexed by `TetrominoKind`
+    pub pieces: [[f32; 3]; 7],
+    pub garbage: [f32; 3],
+    pub special: [f32; 3],
+    /// added to the piece colour for the ghost
+    pub ghost_tint: f32,
+    /// the touch buttons
+    pub button: [f32; 3],
+    pub button_pressed: [f32; 3],
+    /// the menus, and what's behind them when they're over the board
+    pub text: [f32; 3],
+    pub text_selected: [f32; 3],
+    pub backdrop: [f32; 3],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            empty: [0.01; 3],
+            pieces: [
+                [0.19, 0.65, 0.80],
+                [0.80, 0.70, 0.03],
+                [0.26, 0.71, 0.26],
+                [0.80, 0.13, 0.16],
+                [0.35, 0.4, 0.68],
+                [0.80, 0.40, 0.10],
+                [0.68, 0.3, 0.61],
+            ],
+            garbage: [0.4; 3],
+            special: [0.9; 3],
+            ghost_tint: 0.2,
+            button: [0.08; 3],
+            button_pressed: [0.2; 3],
+            text: [0.6; 3],
+            text_selected: [0.95, 0.8, 0.3],
+            backdrop: [0.; 3],
+        }
+    }
+}
+
+impl Palette {
+    pub fn piece(&self, kind: TetrominoKind) -> [f32; 3] {
+        self.pieces[kind as usize]
+    }
+
+    pub fn color(&self, cell: Cell) -> [f32; 3] {
+        match cell {
+            Cell::Empty => self.empty,
+            Cell::Filled(kind) => self.piece(kind),
+            Cell::Garbage => self.garbage,
+            Cell::Ghost(kind) => self.piece(kind).map(|x| x + self.ghost_tint),
+            Cell::Special => self.special,
+        }
+    }
+}
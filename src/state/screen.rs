@@ -0,0 +1,315 @@
+use winit::keyboard::KeyCode;
+
+use super::keymap::Command;
+use crate::tetris::{
+    clock::TICK_RATE,
+    rules::{Kicks180, Rules},
+};
+
+/// ticks counted down before play starts or resumes
+pub const COUNTDOWN: u32 = 3 * TICK_RATE;
+/// lines to clear in a sprint
+pub const SPRINT_LINES: u32 = 40;
+
+/// Where the app is. The game only runs while `Playing`, every other screen is a menu, drawn
+/// over the board or instead of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Screen {
+    Title,
+    ModeSelect,
+    /// ticks left before play starts
+    Countdown(u32),
+    Playing,
+    Paused,
+    Results(Results),
+    /// `paused` if it was opened from the pause menu, which is where it goes back to
+    Settings {
+        paused: bool,
+    },
+}
+
+/// The ways to play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// the endless game, saved on quit and picked back up
+    Marathon,
+    /// clearing `SPRINT_LINES` as fast as possible
+    Sprint,
+    Puzzles,
+}
+
+/// How a game went, for the results screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Results {
+    pub mode: Mode,
+    /// false if it topped out
+    pub cleared: bool,
+    pub lines: u32,
+    pub score: u32,
+    pub ticks: u32,
+}
+
+/// What picking a menu entry does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Item {
+    Start,
+    Settings,
+    Quit,
+    Mode(Mode),
+    Resume,
+    Restart,
+    Retry,
+    Title,
+    Setting(Setting),
+    Back,
+}
+
+/// The rules the settings screen can change, with left and right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Das,
+    Arr,
+    Sdf,
+    Gravity,
+    Irs,
+    Ihs,
+    Kicks180,
+}
+
+/// Moving around the menus, from the arrow keys, enter and escape or from the game's commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+}
+
+/// A line of text on a menu.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub text: String,
+    /// screen pixels per font pixel, shrunk if the line doesn't fit
+    pub scale: usize,
+    pub selected: bool,
+}
+
+impl Line {
+    fn new(text: impl Into<String>, scale: usize) -> Self {
+        Self {
+            text: text.into(),
+            scale,
+            selected: false,
+        }
+    }
+}
+
+impl Screen {
+    pub fn items(&self) -> &'static [Item] {
+        match self {
+            Self::Title => &[Item::Start, Item::Settings, Item::Quit],
+            Self::ModeSelect => &[
+                Item::Mode(Mode::Marathon),
+                Item::Mode(Mode::Sprint),
+                Item::Mode(Mode::Puzzles),
+                Item::Back,
+            ],
+            Self::Countdown(_) | Self::Playing => &[],
+            Self::Paused => &[
+                Item::Resume,
+                Item::Restart,
+                Item::Settings,
+                Item::Title,
+                Item::Quit,
+            ],
+            Self::Results(_) => &[Item::Retry, Item::Title],
+            Self::Settings { .. } => &[
+                Item::Setting(Setting::Das),
+                Item::Setting(Setting::Arr),
+                Item::Setting(Setting::Sdf),
+                Item::Setting(Setting::Gravity),
+                Item::Setting(Setting::Irs),
+                Item::Setting(Setting::Ihs),
+                Item::Setting(Setting::Kicks180),
+                Item::Back,
+            ],
+        }
+    }
+
+    /// whether the board is drawn, behind the menu if there is one
+    pub fn shows_board(&self) -> bool {
+        !matches!(
+            self,
+            Self::Title | Self::ModeSelect | Self::Settings { paused: false }
+        )
+    }
+
+    /// the text to draw, top to bottom, with `selected` the index of the highlighted item
+    pub fn lines(&self, rules: &Rules, selected: usize) -> Vec<Line> {
+        let mut lines = match self {
+            Self::Title => vec![Line::new("TETRUST", 3)],
+            Self::ModeSelect => vec![Line::new("MODE", 2)],
+            // the seconds left, rounded up so it never shows 0
+            Self::Countdown(ticks) => vec![Line::new(ticks.div_ceil(TICK_RATE).to_string(), 5)],
+            Self::Playing => vec![],
+            Self::Paused => vec![Line::new("PAUSED", 2)],
+            Self::Results(results) => results.lines(),
+            Self::Settings { .. } => vec![Line::new("SETTINGS", 2)],
+        };
+
+        let items = self.items();
+        if !items.is_empty() {
+            lines.push(Line::new("", 1));
+        }
+        for (i, item) in items.iter().enumerate() {
+            let label = item.label(rules);
+            // the same width either way so the text doesn't move when it's picked
+            lines.push(if i == selected {
+                Line {
+                    selected: true,
+                    ..Line::new(format!("> {} <", label), 1)
+                }
+            } else {
+                Line::new(format!("  {}  ", label), 1)
+            });
+        }
+        lines
+    }
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Marathon => "MARATHON",
+            Self::Sprint => "SPRINT 40L",
+            Self::Puzzles => "PUZZLES",
+        }
+    }
+}
+
+impl Results {
+    fn lines(&self) -> Vec<Line> {
+        let title = if self.cleared { "CLEAR!" } else { "GAME OVER" };
+        let mut lines = vec![Line::new(title, 2), Line::new(self.mode.name(), 1)];
+        lines.push(Line::new(format!("TIME  {}", time(self.ticks)), 1));
+        lines.push(Line::new(format!("LINES {}", self.lines), 1));
+        if self.mode == Mode::Marathon {
+            lines.push(Line::new(format!("SCORE {}", self.score), 1));
+        }
+        lines
+    }
+}
+
+impl Item {
+    fn label(&self, rules: &Rules) -> String {
+        match self {
+            Self::Start => "START".into(),
+            Self::Settings => "SETTINGS".into(),
+            Self::Quit => "QUIT".into(),
+            Self::Mode(mode) => mode.name().into(),
+            Self::Resume => "RESUME".into(),
+            Self::Restart => "RESTART".into(),
+            Self::Retry => "RETRY".into(),
+            Self::Title => "TITLE".into(),
+            Self::Setting(setting) => format!("{:<9} {:>5}", setting.name(), setting.value(rules)),
+            Self::Back => "BACK".into(),
+        }
+    }
+}
+
+impl Setting {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Das => "DAS",
+            Self::Arr => "ARR",
+            Self::Sdf => "SDF",
+            Self::Gravity => "GRAVITY",
+            Self::Irs => "IRS",
+            Self::Ihs => "IHS",
+            Self::Kicks180 => "180 KICKS",
+        }
+    }
+
+    fn value(&self, rules: &Rules) -> String {
+        let on = |x: bool| if x { "ON" } else { "OFF" }.to_string();
+        match self {
+            Self::Das => rules.das.to_string(),
+            Self::Arr => rules.arr.to_string(),
+            // 0 drops straight down
+            Self::Sdf if rules.sdf == 0 => "INF".into(),
+            Self::Sdf => rules.sdf.to_string(),
+            Self::Gravity => rules.gravity.to_string(),
+            Self::Irs => on(rules.irs),
+            Self::Ihs => on(rules.ihs),
+            Self::Kicks180 => match rules.kicks_180 {
+                Kicks180::Off => "OFF",
+                Kicks180::Nudge => "NUDGE",
+                Kicks180::SrsPlus => "SRS+",
+            }
+            .into(),
+        }
+    }
+
+    /// steps the value up or down, switches go either way and lists wrap around
+    pub fn adjust(&self, rules: &mut Rules, step: i32) {
+        let add =
+            |value: u32, min: u32, max: u32| value.saturating_add_signed(step).clamp(min, max);
+        match self {
+            Self::Das => rules.das = add(rules.das, 0, 30),
+            Self::Arr => rules.arr = add(rules.arr, 0, 10),
+            Self::Sdf => rules.sdf = add(rules.sdf, 0, 40),
+            Self::Gravity => rules.gravity = add(rules.gravity, 1, 120),
+            Self::Irs => rules.irs = !rules.irs,
+            Self::Ihs => rules.ihs = !rules.ihs,
+            Self::Kicks180 => {
+                const KICKS: [Kicks180; 3] = [Kicks180::Off, Kicks180::Nudge, Kicks180::SrsPlus];
+                let i = KICKS
+                    .iter()
+                    .position(|&x| x == rules.kicks_180)
+                    .unwrap_or(0);
+                rules.kicks_180 = KICKS[(i as i32 + step).rem_euclid(3) as usize];
+            }
+        }
+    }
+}
+
+impl MenuInput {
+    pub fn from_key(key: KeyCode) -> Option<Self> {
+        match key {
+            KeyCode::ArrowUp | KeyCode::KeyW => Some(Self::Up),
+            KeyCode::ArrowDown | KeyCode::KeyS => Some(Self::Down),
+            KeyCode::ArrowLeft | KeyCode::KeyA => Some(Self::Left),
+            KeyCode::ArrowRight | KeyCode::KeyD => Some(Self::Right),
+            KeyCode::Enter | KeyCode::NumpadEnter | KeyCode::Space => Some(Self::Confirm),
+            KeyCode::Escape | KeyCode::Backspace => Some(Self::Back),
+            _ => None,
+        }
+    }
+
+    /// for controllers and touch, and keys rebound away from the arrows, going by the default
+    /// keys: up rotates clockwise, down counterclockwise
+    pub fn from_command(command: Command) -> Option<Self> {
+        match command {
+            Command::RotateCw => Some(Self::Up),
+            Command::RotateCcw | Command::SoftDrop | Command::SonicDrop => Some(Self::Down),
+            Command::Left => Some(Self::Left),
+            Command::Right => Some(Self::Right),
+            Command::HardDrop => Some(Self::Confirm),
+            Command::Pause | Command::Hold => Some(Self::Back),
+            _ => None,
+        }
+    }
+}
+
+/// ticks as minutes, seconds and hundredths
+fn time(ticks: u32) -> String {
+    let hundredths = ticks as u64 * 100 / TICK_RATE as u64;
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
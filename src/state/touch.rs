@@ -0,0 +1,245 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use winit::{
+    dpi::PhysicalPosition,
+    event::{Touch, TouchPhase},
+};
+
+use super::{keymap::Command, layout::Layout};
+
+/// how long a touch can last and still be a tap or a flick
+const QUICK: Duration = Duration::from_millis(250);
+/// how far a tap can wander, in cells
+const TAP_DISTANCE: f32 = 0.5;
+/// how far a flick has to go, in cells
+const FLICK_DISTANCE: f32 = 2.;
+/// how far down a finger has to drag to soft drop, in cells
+const SOFT_DISTANCE: f32 = 1.5;
+
+/// what a finger on the screen is doing
+#[derive(Debug, Clone, Copy)]
+enum Gesture {
+    /// holding down a touch button
+    Button(Command),
+    /// dragging over the board, every cell sideways is a move
+    Swipe {
+        start: PhysicalPosition<f64>,
+        time: Instant,
+        /// columns moved so far
+        columns: i32,
+        soft: bool,
+    },
+}
+
+/// Touch buttons and gestures, turned into the same commands keys are bound to. Touching a
+/// button holds its command down until the finger lifts. Anywhere else, dragging sideways moves
+/// a column per cell, dragging down soft drops, a tap rotates (counterclockwise on the left half
+/// of the screen, clockwise on the right), a flick down hard drops and a flick up holds.
+#[derive(Debug, Default)]
+pub struct TouchControls {
+    /// set by the first touch, so the buttons only show up on touchscreens
+    pub enabled: bool,
+    touches: HashMap<u64, Gesture>,
+}
+
+impl TouchControls {
+    /// returns the commands pressed and released, in order
+    pub fn handle(&mut self, touch: &Touch, layout: &Layout) -> Vec<(Command, bool)> {
+        self.enabled = true;
+        let mut commands = vec![];
+        let (x, y) = layout.ndc(touch.location);
+
+        match touch.phase {
+            TouchPhase::Started => {
+                let gesture = match layout
+                    .buttons()
+                    .iter()
+                    .find(|(rect, _)| rect.contains(x, y))
+                {
+                    Some(&(_, command)) => {
+                        commands.push((command, true));
+                        Gesture::Button(command)
+                    }
+                    None => Gesture::Swipe {
+                        start: touch.location,
+                        time: Instant::now(),
+                        columns: 0,
+                        soft: false,
+                    },
+                };
+                self.touches.insert(touch.id, gesture);
+            }
+            TouchPhase::Moved => {
+                if let Some(Gesture::Swipe {
+                    start,
+                    columns,
+                    soft,
+                    ..
+                }) = self.touches.get_mut(&touch.id)
+                {
+                    let (dx, dy) = cells(*start, touch.location, layout);
+                    let target = dx.trunc() as i32;
+                    while *columns != target {
+                        let (command, step) = if target > *columns {
+                            (Command::Right, 1)
+                        } else {
+                            (Command::Left, -1)
+                        };
+                        commands.extend([(command, true), (command, false)]);
+                        *columns += step;
+                    }
+
+                    let down = dy > SOFT_DISTANCE && dy > dx.abs();
+                    if down != *soft {
+                        *soft = down;
+                        commands.push((Command::SoftDrop, down));
+                    }
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => match self.touches.remove(&touch.id) {
+                Some(Gesture::Button(command)) => commands.push((command, false)),
+                Some(Gesture::Swipe {
+                    start, time, soft, ..
+                }) => {
+                    if soft {
+                        commands.push((Command::SoftDrop, false));
+                    }
+                    let (dx, dy) = cells(start, touch.location, layout);
+                    let quick = touch.phase == TouchPhase::Ended && time.elapsed() < QUICK;
+                    let vertical = dy.abs() > dx.abs();
+
+                    let command = if !quick {
+                        None
+                    } else if dx.hypot(dy) < TAP_DISTANCE {
+                        Some(if x < 0. {
+                            Command::RotateCcw
+                        } else {
+                            Command::RotateCw
+                        })
+                    } else if vertical && dy > FLICK_DISTANCE {
+                        Some(Command::HardDrop)
+                    } else if vertical && dy < -FLICK_DISTANCE {
+                        Some(Command::Hold)
+                    } else {
+                        None
+                    };
+                    if let Some(command) = command {
+                        commands.extend([(command, true), (command, false)]);
+                    }
+                }
+                None => {}
+            },
+        }
+        commands
+    }
+
+    /// whether a finger is on the button for the command
+    pub fn is_pressed(&self, command: Command) -> bool {
+        self.touches
+            .values()
+            .any(|gesture| matches!(gesture, Gesture::Button(x) if *x == command))
+    }
+}
+
+/// how far the finger went in cells, down being positive
+fn cells(start: PhysicalPosition<f64>, end: PhysicalPosition<f64>, layout: &Layout) -> (f32, f32) {
+    let cell = layout.cell_pixels();
+    (
+        (end.x - start.x) as f32 / cell,
+        (end.y - start.y) as f32 / cell,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::DeviceId;
+
+    use super::*;
+
+    /// a square window, so the buttons are at the sides of the board
+    fn layout() -> Layout {
+        Layout::new(1000, 1000)
+    }
+
+    /// a finger `cells` away from the middle of the board
+    fn at(phase: TouchPhase, cells: (f64, f64)) -> Touch {
+        let cell = layout().cell_pixels() as f64;
+        Touch {
+            device_id: DeviceId::dummy(),
+            phase,
+            location: PhysicalPosition::new(500. + cells.0 * cell, 500. + cells.1 * cell),
+            force: None,
+            id: 0,
+        }
+    }
+
+    /// the commands pressed
+    fn touch(controls: &mut TouchControls, phase: TouchPhase, cells: (f64, f64)) -> Vec<Command> {
+        controls
+            .handle(&at(phase, cells), &layout())
+            .into_iter()
+            .filter_map(|(command, pressed)| pressed.then_some(command))
+            .collect()
+    }
+
+    #[test]
+    fn a_column_per_cell_swiped() {
+        let mut controls = TouchControls::default();
+        touch(&mut controls, TouchPhase::Started, (0., 0.));
+        assert_eq!(
+            touch(&mut controls, TouchPhase::Moved, (2.5, 0.)),
+            [Command::Right, Command::Right]
+        );
+        assert_eq!(
+            touch(&mut controls, TouchPhase::Moved, (-1.2, 0.)),
+            [Command::Left, Command::Left, Command::Left]
+        );
+        assert!(touch(&mut controls, TouchPhase::Moved, (-1.9, 0.)).is_empty());
+    }
+
+    #[test]
+    fn soft_drops_past_the_threshold() {
+        let mut controls = TouchControls::default();
+        touch(&mut controls, TouchPhase::Started, (0., 0.));
+        assert!(touch(&mut controls, TouchPhase::Moved, (0., 1.4)).is_empty());
+        assert_eq!(
+            touch(&mut controls, TouchPhase::Moved, (0., 1.6)),
+            [Command::SoftDrop]
+        );
+        // let go without going far enough to flick
+        assert_eq!(
+            controls.handle(&at(TouchPhase::Ended, (0., 1.6)), &layout()),
+            [(Command::SoftDrop, false)]
+        );
+    }
+
+    #[test]
+    fn taps_and_flicks() {
+        let mut controls = TouchControls::default();
+        let mut quick = |cells| {
+            touch(&mut controls, TouchPhase::Started, (0., 0.));
+            touch(&mut controls, TouchPhase::Ended, cells)
+        };
+        assert_eq!(quick((0.2, 0.)), [Command::RotateCw]);
+        assert_eq!(quick((0., 3.)), [Command::HardDrop]);
+        assert_eq!(quick((0., -3.)), [Command::Hold]);
+        // too far for a tap, not far enough for a flick
+        assert!(quick((0., 1.)).is_empty());
+
+        touch(&mut controls, TouchPhase::Started, (0., 0.));
+        std::thread::sleep(QUICK);
+        assert!(touch(&mut controls, TouchPhase::Ended, (0., 0.)).is_empty());
+    }
+
+    #[test]
+    fn taps_rotate_by_the_half_of_the_screen() {
+        let mut controls = TouchControls::default();
+        for (x, command) in [(-2., Command::RotateCcw), (2., Command::RotateCw)] {
+            touch(&mut controls, TouchPhase::Started, (x, 0.));
+            assert_eq!(touch(&mut controls, TouchPhase::Ended, (x, 0.)), [command]);
+        }
+    }
+}
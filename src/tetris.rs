@@ -0,0 +1,688 @@
+pub mod action;
+pub mod bag;
+pub mod bitboard;
+pub mod board;
+pub mod cell;
+pub mod clock;
+pub mod diagram;
+pub mod event;
+pub mod fumen;
+pub mod handling;
+pub mod phase;
+pub mod point;
+pub mod puzzle;
+pub mod rules;
+pub mod tetromino;
+mod view;
+
+use serde::{Deserialize, Serialize};
+
+use action::Action;
+use bag::Bag;
+use board::Board;
+use cell::Cell;
+use event::Event;
+use phase::Phase;
+use point::Point;
+use rules::{HoldRule, Rules};
+use tetromino::{Tetromino, tetromino_kind::TetrominoKind};
+pub use view::GameView;
+
+/// points for clearing 0 to 4 lines at once
+const LINE_SCORES: [u32; 5] = [0, 100, 300, 500, 800];
+/// bonus points for leaving the board empty
+const PERFECT_CLEAR_SCORE: u32 = 2000;
+/// undrained events kept before the oldest are dropped
+const MAX_EVENTS: usize = 256;
+
+/// A game of tetris. The events it raises pile up until `drain_events` is called, so anything
+/// driving it should drain them every tick; only the newest `MAX_EVENTS` are kept otherwise.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Tetris {
+    pub board: Board,
+    pub tetro: Tetromino,
+    pub bag: Bag,
+    #[serde(default)]
+    rules: Rules,
+    #[serde(default)]
+    phase: Phase,
+
+    moved: bool,
+    hold: Option<TetrominoKind>,
+    fall_timer: u32,
+    /// ticks the piece has been resting on the stack
+    #[serde(default)]
+    lock_timer: u32,
+    /// how many times the lock delay was started over for this piece
+    #[serde(default)]
+    lock_resets: u32,
+    lines: u32,
+    #[serde(default)]
+    score: u32,
+    #[serde(default)]
+    perfect_clears: u32,
+    /// whether the last thing the piece did was rotate, for spotting T-spins
+    #[serde(default)]
+    rotated: bool,
+    /// the rotations and hold held down, the newest last, for IRS and IHS
+    #[serde(skip)]
+    spawn_inputs: Vec<Action>,
+
+    #[serde(skip)]
+    events: Vec<Event>,
+}
+
+impl Tetris {
+    pub fn new() -> Self {
+        Self::with_bag(Bag::new())
+    }
+
+    /// a game whose pieces always come in the same order for the same seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_bag(Bag::with_seed(seed))
+    }
+
+    /// a new game played by `rules`
+    pub fn with_rules(rules: Rules) -> Self {
+        let mut bag = Bag::new();
+        bag.set_randomizer(rules.randomizer);
+        Self {
+            rules,
+            ..Self::with_bag(bag)
+        }
+    }
+
+    /// A game on `board` that deals `pieces` in order before going on with random bags, the
+    /// first one straight into play.
+    pub fn with_pieces(
+        board: Board,
+        pieces: &[TetrominoKind],
+        hold: Option<TetrominoKind>,
+    ) -> Self {
+        let mut tetris = Self::with_bag(Bag::with_pieces(pieces));
+        tetris.board = board;
+        tetris.hold = hold;
+        tetris
+    }
+
+    fn with_bag(mut bag: Bag) -> Self {
+        let tetro = Tetromino::from_kind(bag.next());
+
+        Self {
+            bag,
+            tetro,
+            ..Default::default()
+        }
+    }
+
+    pub fn rotate(&mut self, radians: f32) {
+        let new = self.board.rotate(&self.tetro, radians);
+        self.turn(new);
+    }
+
+    /// turns the piece half way round with the kicks the rules pick
+    pub fn rotate_180(&mut self) {
+        let new = self.board.rotate_180(&self.tetro, self.rules.kicks_180);
+        self.turn(new);
+    }
+
+    fn turn(&mut self, new: Option<Tetromino>) {
+        if let Some(new) = new {
+            self.tetro = new;
+            self.rotated = true;
+            self.reset_lock();
+        }
+    }
+
+    /// returns lines cleared if reset
+    pub fn hard_drop(&mut self) -> Option<u32> {
+        self.drop_tetro();
+        self.finish()
+    }
+    pub fn fall(&mut self) {
+        if self.fall_tetro(None) {
+            self.tetro.anchor.y += 1.0;
+            self.rotated = false;
+            self.lock_timer = 0;
+        } else {
+            self.finish();
+        }
+    }
+
+    pub fn move_x(&mut self, x: f32) -> bool {
+        let before = self.fall_tetro(None);
+        self.tetro.anchor.x += x;
+
+        let is_valid = self.is_valid(None);
+        if is_valid {
+            self.rotated = false;
+            self.reset_lock();
+        } else {
+            self.tetro.anchor.x -= x;
+        }
+
+        if before && !self.fall_tetro(None) {
+            self.fall_timer = 0;
+        }
+        is_valid
+    }
+
+    /// This function checks where a tetromino would hard fall to and returns the amount of y you
+    /// have to add to reach that point.
+    fn hard_fall_tetro(&self, tetro: Option<&Tetromino>) -> f32 {
+        self.board.drop_distance(tetro.unwrap_or(&self.tetro))
+    }
+    /// drops the piece as far as it goes
+    fn drop_tetro(&mut self) {
+        let distance = self.hard_fall_tetro(None);
+        if distance > 0. {
+            self.tetro.anchor.y += distance;
+            self.rotated = false;
+            self.lock_timer = 0;
+        }
+    }
+
+    /// starts the lock delay over after the piece moved on the stack, as often as the rules allow
+    fn reset_lock(&mut self) {
+        if self.lock_timer > 0 && self.lock_resets < self.rules.lock_resets {
+            self.lock_timer = 0;
+            self.lock_resets += 1;
+        }
+    }
+
+    /// A T that rotated into place with at least three of the four cells diagonal to its
+    /// centre taken, the walls and floor counting as taken.
+    fn is_t_spin(&self) -> bool {
+        if self.tetro.kind != TetrominoKind::T || !self.rotated {
+            return false;
+        }
+        let (x, y) = (self.tetro.anchor.x as isize, self.tetro.anchor.y as isize);
+        let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)]
+            .iter()
+            .filter(|(dx, dy)| self.board.bits().filled(x + dx, y + dy))
+            .count();
+        corners >= 3
+    }
+
+    /// This function checks whether a tetromino can possibly fall without causing collision
+    fn fall_tetro(&self, tetro: Option<&Tetromino>) -> bool {
+        let mut tro = *tetro.unwrap_or(&self.tetro);
+        tro.anchor.y += 1.0;
+        self.is_valid(Some(&tro))
+    }
+
+    /// Locks the piece and scores it, then clears the rows and spawns the next piece once the
+    /// rules' delays are up. returns lines cleared if reset
+    fn finish(&mut self) -> Option<u32> {
+        let t_spin = self.is_t_spin();
+        self.rotated = false;
+        self.board.engrave(&self.tetro);
+
+        // scored straight away, the rows only go once the line clear delay is up
+        let mut cleared = *self.board.bits();
+        let lines = cleared.clear_lines();
+        self.lines += lines;
+        self.score += LINE_SCORES[lines as usize];
+
+        let perfect_clear = lines > 0 && cleared.is_empty();
+        if perfect_clear {
+            self.perfect_clears += 1;
+            self.score += PERFECT_CLEAR_SCORE;
+        }
+        self.push_event(Event::Lock {
+            lines,
+            perfect_clear,
+            t_spin,
+        });
+
+        self.moved = false;
+        self.lock_timer = 0;
+        self.lock_resets = 0;
+
+        if lines > 0 && self.rules.line_clear_delay > 0 {
+            self.phase = Phase::LineClear(self.rules.line_clear_delay);
+            return None;
+        }
+        self.board.clear_lines();
+        self.entry_delay()
+    }
+
+    /// waits out the ARE if the rules have one, otherwise spawns the next piece straight away.
+    /// returns lines cleared if reset
+    fn entry_delay(&mut self) -> Option<u32> {
+        if self.rules.are > 0 {
+            self.phase = Phase::Are(self.rules.are);
+            return None;
+        }
+        self.spawn()
+    }
+
+    /// puts the next piece in play, returns lines cleared if it doesn't fit and the game resets
+    fn spawn(&mut self) -> Option<u32> {
+        self.phase = Phase::Falling;
+        self.tetro = Tetromino::from_kind(self.bag.next());
+        // IHS comes first, so it's whichever piece ends up in play that has to fit
+        if self.rules.ihs && self.spawn_inputs.contains(&Action::Hold) {
+            self.hold();
+        }
+        if !self.is_valid(None) {
+            let (lines, score) = (self.lines, self.score);
+            // the lock that topped out still gets reported
+            let events = std::mem::take(&mut self.events);
+            self.reset();
+            self.events = events;
+            self.push_event(Event::GameOver { lines, score });
+            return Some(lines);
+        }
+        self.initial_rotation();
+        None
+    }
+
+    /// false between a piece locking and the next one spawning, when inputs do nothing
+    pub fn in_play(&self) -> bool {
+        self.phase == Phase::Falling
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// IRS: the last rotation held down as the piece spawns turns it, after IHS has swapped it
+    fn initial_rotation(&mut self) {
+        let rotation = self
+            .spawn_inputs
+            .iter()
+            .rev()
+            .find_map(|action| match action {
+                Action::Rotate(degrees) => Some(*degrees),
+                _ => None,
+            });
+        if self.rules.irs
+            && let Some(degrees) = rotation
+        {
+            self.process_action(Action::Rotate(degrees));
+        }
+    }
+
+    /// Tells the game a rotation or hold input went down or up, so it can be played on the next
+    /// piece as it spawns, see `Rules::irs` and `Rules::ihs`. Pressing it still has to be played
+    /// with `process_action` as usual.
+    pub fn set_input_held(&mut self, action: Action, held: bool) {
+        self.spawn_inputs.retain(|&x| x != action);
+        if held {
+            self.spawn_inputs.push(action);
+        }
+    }
+
+    fn is_valid(&self, tetro: Option<&Tetromino>) -> bool {
+        self.board.is_valid(tetro.unwrap_or(&self.tetro))
+    }
+
+    /// Advances the game by a single tick.
+    /// returns true if something changed; signaling to the renderer that it needs to update
+    pub fn tick(&mut self, soft: bool) -> bool {
+        if let Phase::LineClear(left) | Phase::Are(left) = &mut self.phase {
+            *left -= 1;
+            if *left > 0 {
+                return false;
+            }
+            if let Phase::LineClear(_) = self.phase {
+                self.board.clear_lines();
+                self.entry_delay();
+            } else {
+                self.spawn();
+            }
+            self.fall_timer = 0;
+            return true;
+        }
+
+        if !self.fall_tetro(None) {
+            // resting on the stack, it locks once the lock delay is up
+            self.lock_timer += 1;
+            if self.lock_timer > self.rules.lock_delay {
+                self.finish();
+                self.fall_timer = 0;
+                return true;
+            }
+            return false;
+        }
+
+        let time = if soft {
+            self.rules.soft_gravity()
+        } else {
+            self.rules.gravity
+        };
+        if time == 0 {
+            self.drop_tetro();
+            self.fall_timer = 0;
+            return true;
+        }
+        self.fall_timer += 1;
+        if self.fall_timer >= time {
+            self.fall();
+            self.fall_timer = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn hold(&mut self) {
+        if self.can_hold() {
+            let kind = self.tetro.kind;
+
+            if let Some(hold) = self.hold {
+                self.tetro = Tetromino::from_kind(hold);
+            } else {
+                self.tetro = Tetromino::from_kind(self.bag.next());
+            }
+
+            self.hold = Some(kind);
+            self.moved = self.rules.hold == HoldRule::Once;
+            self.rotated = false;
+            self.lock_timer = 0;
+            self.lock_resets = 0;
+        }
+    }
+
+    // returns the amount of lines if the game reset
+    pub fn process_action(&mut self, action: Action) -> Option<u32> {
+        if !self.in_play() {
+            return None;
+        }
+        match action {
+            Action::Move(x) => {
+                self.move_x(x as f32);
+                None
+            }
+            Action::Rotate(degrees) if degrees.rem_euclid(360) == 180 => {
+                self.rotate_180();
+                None
+            }
+            Action::Rotate(degrees) => {
+                self.rotate((degrees as f32).to_radians());
+                None
+            }
+            Action::HardDrop => self.hard_drop(),
+            Action::SoftDrop => {
+                self.drop_tetro();
+                self.fall_timer = 0;
+                None
+            }
+            Action::Hold => {
+                self.hold();
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_full_board(&self) -> [[Cell; 10]; 20] {
+        let mut ghost = self.tetro;
+        ghost.anchor.y += self.hard_fall_tetro(Some(&ghost));
+        self.board_with_ghost(&ghost)
+    }
+
+    /// the board with the piece in play and its ghost drawn where `ghost` is, for previewing
+    /// somewhere other than straight down. Between pieces there's neither, and rows waiting to
+    /// be cleared show up as `Cell::Special`.
+    pub fn board_with_ghost(&self, ghost: &Tetromino) -> [[Cell; 10]; 20] {
+        let mut board = *self.board;
+        if !self.in_play() {
+            for y in self.board.bits().full_rows() {
+                board[y] = [Cell::Special; 10];
+            }
+            return board;
+        }
+
+        for point in ghost
+            .get_points_vec()
+            .iter()
+            .map(|x| Point::new(x.x as usize, x.y as usize))
+        {
+            board[point.y][point.x] = Cell::Ghost(self.tetro.kind);
+        }
+        for point in self
+            .tetro
+            .get_points_vec()
+            .iter()
+            .map(|x| Point::new(x.x as usize, x.y as usize))
+        {
+            board[point.y][point.x] = Cell::Filled(self.tetro.kind);
+        }
+
+        board
+    }
+
+    /// a read-only snapshot of what a player can see, for bots
+    pub fn view(&self) -> GameView<'_> {
+        GameView {
+            board: &self.board,
+            piece: self.tetro,
+            hold: self.hold,
+            can_hold: self.can_hold(),
+            queue: self.bag.queue(self.rules.queue),
+            kicks_180: self.rules.kicks_180,
+        }
+    }
+
+    /// false if the rules don't allow hold or it was already used for this piece
+    pub fn can_hold(&self) -> bool {
+        self.rules.hold != HoldRule::Off && !self.moved
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// switches the game over to other rules, the pieces already picked stay the same
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.bag.set_randomizer(rules.randomizer);
+        self.rules = rules;
+    }
+
+    /// the piece in hold, if any
+    pub fn held(&self) -> Option<TetrominoKind> {
+        self.hold
+    }
+
+    /// lines cleared since the game started
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// how many times the board was cleared out completely
+    pub fn perfect_clears(&self) -> u32 {
+        self.perfect_clears
+    }
+
+    /// takes the events that happened since the last call
+    pub fn drain_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn push_event(&mut self, event: Event) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.remove(0);
+        }
+        self.events.push(event);
+    }
+
+    /// swaps in a board, say a setup to practice, keeping the evaluator weights. the piece in
+    /// play goes back to its spawn position
+    pub fn load_board(&mut self, mut board: Board) {
+        board.set_weights(self.board.weights());
+        self.board = board;
+        self.tetro = Tetromino::from_kind(self.tetro.kind);
+        self.phase = Phase::Falling;
+        self.fall_timer = 0;
+    }
+
+    /// starts a new game, keeping the rules and the evaluator weights. Events from the old game
+    /// that weren't drained are dropped
+    pub fn reset(&mut self) {
+        let weights = self.board.weights();
+        let spawn_inputs = std::mem::take(&mut self.spawn_inputs);
+        *self = Self::with_rules(self.rules);
+        self.board.set_weights(weights);
+        self.spawn_inputs = spawn_inputs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bot::movegen, tetris::diagram::Diagram};
+
+    /// plays the piece in the diagram into the spot covering `cells`, found by movegen, and
+    /// returns the events
+    fn play_into(diagram: &str, cells: [(isize, isize); 4]) -> Vec<Event> {
+        let diagram: Diagram = diagram.parse().unwrap();
+        let mut pieces = vec![diagram.piece.unwrap()];
+        pieces.extend(&diagram.queue);
+        let mut tetris = Tetris::with_pieces(diagram.board, &pieces, diagram.hold);
+
+        let mut cells = cells.map(|(x, y)| Point::new(x, y));
+        cells.sort_by_key(|point| (point.y, point.x));
+        let placement =
+            movegen::placements(tetris.board.bits(), &tetris.tetro, tetris.rules.kicks_180)
+                .into_iter()
+                .find(|x| {
+                    let mut found = x.piece.cells();
+                    found.sort_by_key(|point| (point.y, point.x));
+                    found == cells
+                })
+                .expect("the spot is reachable");
+        for action in placement.path {
+            tetris.process_action(action);
+        }
+        tetris.drain_events()
+    }
+
+    #[test]
+    fn t_spin_double() {
+        let events = play_into(
+            "piece: T\n###.......\n#...######\n##.#######",
+            [(1, 18), (2, 18), (3, 18), (2, 19)],
+        );
+        assert_eq!(
+            events,
+            [Event::Lock {
+                lines: 2,
+                perfect_clear: false,
+                t_spin: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn no_t_spin_without_turning() {
+        // the same slot with nothing over it, so the T drops straight in
+        let events = play_into(
+            "piece: T\n#...######\n##.#######",
+            [(1, 18), (2, 18), (3, 18), (2, 19)],
+        );
+        assert_eq!(
+            events,
+            [Event::Lock {
+                lines: 2,
+                perfect_clear: true,
+                t_spin: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn perfect_clear() {
+        let events = play_into(
+            "piece: I\n#########.\n#########.\n#########.\n#########.",
+            [(9, 16), (9, 17), (9, 18), (9, 19)],
+        );
+        assert_eq!(
+            events,
+            [Event::Lock {
+                lines: 4,
+                perfect_clear: true,
+                t_spin: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn tops_out_when_the_held_piece_does_not_fit() {
+        use TetrominoKind::*;
+        let mut tetris = Tetris::with_pieces(Board::default(), &[I, I], Some(O));
+        tetris.set_rules(Rules {
+            ihs: true,
+            ..Rules::default()
+        });
+        tetris.process_action(Action::Move(-3));
+        // under the spawned O but not the I
+        tetris.board.set(4, 1, Cell::Garbage);
+        tetris.set_input_held(Action::Hold, true);
+
+        assert!(tetris.process_action(Action::HardDrop).is_some());
+        assert!(
+            tetris
+                .drain_events()
+                .iter()
+                .any(|event| matches!(event, Event::GameOver { .. }))
+        );
+    }
+
+    #[test]
+    fn undrained_events_are_capped_and_reset() {
+        let mut tetris = Tetris::with_seed(0);
+        // hard drops in place top out every few pieces, over and over
+        for _ in 0..2 * MAX_EVENTS {
+            tetris.process_action(Action::HardDrop);
+        }
+        assert_eq!(tetris.events.len(), MAX_EVENTS);
+        assert!(
+            tetris
+                .events
+                .iter()
+                .any(|event| matches!(event, Event::GameOver { .. }))
+        );
+
+        tetris.process_action(Action::HardDrop);
+        tetris.reset();
+        assert!(tetris.drain_events().is_empty());
+    }
+
+    #[test]
+    fn falls_every_gravity_ticks() {
+        let mut tetris = Tetris::with_seed(0);
+        tetris.set_rules(Rules {
+            gravity: 3,
+            ..Rules::default()
+        });
+        let y = tetris.tetro.anchor.y;
+        for row in 1..=2 {
+            assert!(!tetris.tick(false));
+            assert!(!tetris.tick(false));
+            assert!(tetris.tick(false));
+            assert_eq!(tetris.tetro.anchor.y, y + row as f32);
+        }
+    }
+
+    #[test]
+    fn locks_after_resting_for_the_lock_delay() {
+        let mut tetris = Tetris::with_seed(0);
+        tetris.set_rules(Rules {
+            lock_delay: 3,
+            ..Rules::default()
+        });
+        tetris.drop_tetro();
+        for _ in 0..3 {
+            assert!(!tetris.tick(false));
+        }
+        assert!(tetris.drain_events().is_empty());
+        assert!(tetris.tick(false));
+        assert!(matches!(tetris.drain_events()[..], [Event::Lock { .. }]));
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Move(i32),
+    Rotate(i32),
+    HardDrop,
+    SoftDrop,
+    Hold,
+    None,
+}
@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{rules::Randomizer, tetromino::tetromino_kind::TetrominoKind};
+
+type SevenBag = [TetrominoKind; 7];
+
+/// the rng lives inside the bag so the upcoming pieces are part of the saved game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bag {
+    index: usize,
+    bag: SevenBag,
+    rng: ChaCha8Rng,
+    /// pieces dealt in order before the shuffled bags, for puzzles
+    #[serde(default)]
+    preset: VecDeque<TetrominoKind>,
+    #[serde(default)]
+    randomizer: Randomizer,
+}
+
+impl Default for Bag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bag {
+    pub fn new() -> Self {
+        Self::from_rng(ChaCha8Rng::from_rng(&mut rand::rng()))
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// deals `pieces` in order first, then carries on with random bags
+    pub fn with_pieces(pieces: &[TetrominoKind]) -> Self {
+        Self {
+            preset: pieces.iter().copied().collect(),
+            ..Self::new()
+        }
+    }
+
+    fn from_rng(mut rng: ChaCha8Rng) -> Self {
+        Self {
+            index: 0,
+            bag: get_bag(&mut rng),
+            rng,
+            preset: VecDeque::new(),
+            randomizer: Randomizer::default(),
+        }
+    }
+
+    /// changes how the pieces after the ones already picked are chosen
+    pub fn set_randomizer(&mut self, randomizer: Randomizer) {
+        self.randomizer = randomizer;
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> TetrominoKind {
+        if let Some(kind) = self.preset.pop_front() {
+            return kind;
+        }
+        if self.randomizer == Randomizer::Random {
+            return self.rng.random_range(0..7u8).into();
+        }
+        let prevt = self.bag[self.index];
+
+        self.index += 1;
+        if self.index >= 7 {
+            self.bag.shuffle(&mut self.rng);
+            self.index = 0;
+        }
+
+        prevt
+    }
+
+    /// the next `len` pieces, without taking them out of the bag
+    pub fn queue(&self, len: usize) -> Vec<TetrominoKind> {
+        let mut bag = self.clone();
+        (0..len).map(|_| bag.next()).collect()
+    }
+}
+
+fn get_bag(rng: &mut ChaCha8Rng) -> SevenBag {
+    let mut bag = (0..7)
+        .enumerate()
+        .fold([TetrominoKind::default(); 7], |mut acc, (i, x)| {
+            acc[i] = x.into();
+            acc
+        });
+    bag.shuffle(rng);
+    bag
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    point::Point,
+    rules::Kicks180,
+    tetromino::{Tetromino, tetromino_kind::TetrominoKind},
+};
+
+pub const WIDTH: usize = 10;
+pub const HEIGHT: usize = 20;
+const FULL_ROW: u16 = (1 << WIDTH) - 1;
+
+/// TETR.IO's SRS+ kicks for a half turn, tried in order, by the orientation it starts from:
+/// spawn, right, reverse, left. They're (x, y) with y going up like the published table.
+const SRS_PLUS_180: [[(f32, f32); 6]; 4] = [
+    [(0., 0.), (0., 1.), (1., 1.), (-1., 1.), (1., 0.), (-1., 0.)],
+    [(0., 0.), (1., 0.), (1., 2.), (1., 1.), (0., 2.), (0., 1.)],
+    [
+        (0., 0.),
+        (0., -1.),
+        (-1., -1.),
+        (1., -1.),
+        (-1., 0.),
+        (1., 0.),
+    ],
+    [
+        (0., 0.),
+        (-1., 0.),
+        (-1., 2.),
+        (-1., 1.),
+        (0., 2.),
+        (0., 1.),
+    ],
+];
+
+/// Which cells of the board are taken, one bit per cell and one integer per row, with row 0 at
+/// the top and bit x for column x. It's what collision, line clears and the evaluator work on,
+/// and it's small enough to copy for every position a search looks at.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BitBoard {
+    pub rows: [u16; HEIGHT],
+}
+
+impl BitBoard {
+    /// whether the cell is taken, everything outside the board counts as taken
+    pub fn filled(&self, x: isize, y: isize) -> bool {
+        if !(0..WIDTH as isize).contains(&x) || !(0..HEIGHT as isize).contains(&y) {
+            return true;
+        }
+        self.rows[y as usize] & (1 << x) != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize) {
+        self.rows[y] |= 1 << x;
+    }
+
+    /// checks that the cells are inside the board and not taken
+    pub fn fits(&self, cells: &[Point<isize>]) -> bool {
+        cells.iter().all(|point| !self.filled(point.x, point.y))
+    }
+
+    /// checks that the tetromino is inside the board and doesn't overlap anything
+    pub fn is_valid(&self, tetro: &Tetromino) -> bool {
+        self.fits(&tetro.cells())
+    }
+
+    /// This function checks where a tetromino would hard fall to and returns the amount of y you
+    /// have to add to reach that point.
+    pub fn drop_distance(&self, tetro: &Tetromino) -> f32 {
+        let mut cells = tetro.cells();
+        let mut diff = 0.0;
+        loop {
+            for point in cells.iter_mut() {
+                point.y += 1;
+            }
+            if !self.fits(&cells) {
+                return diff;
+            }
+            diff += 1.0;
+        }
+    }
+
+    /// rotates the tetromino, nudging it by a cell in each direction if it doesn't fit in place.
+    /// returns None if none of the nudges fit either
+    pub fn rotate(&self, tetro: &Tetromino, radians: f32) -> Option<Tetromino> {
+        let mut new = *tetro;
+        new.rotate(radians);
+
+        for y in [0.0, 1.0, -1.0] {
+            for x in [0.0, 1.0, -1.0] {
+                let mut kicked = new;
+                kicked.anchor.x += x;
+                kicked.anchor.y += y;
+
+                if self.is_valid(&kicked) {
+                    return Some(kicked);
+                }
+            }
+        }
+        None
+    }
+
+    /// turns the tetromino half way round with the given kicks, None if it can't
+    pub fn rotate_180(&self, tetro: &Tetromino, kicks: Kicks180) -> Option<Tetromino> {
+        match kicks {
+            Kicks180::Off => None,
+            Kicks180::Nudge => self.rotate(tetro, std::f32::consts::PI),
+            Kicks180::SrsPlus => {
+                let mut new = *tetro;
+                new.rotate(std::f32::consts::PI);
+                // the T spawns pointing down, upside down from the guideline's, so its
+                // orientations are two off from the table's
+                let from = match tetro.kind {
+                    TetrominoKind::T => (tetro.rotation + 2) % 4,
+                    _ => tetro.rotation,
+                };
+                SRS_PLUS_180[from as usize].iter().find_map(|&(x, y)| {
+                    let mut kicked = new;
+                    kicked.anchor.x += x;
+                    kicked.anchor.y -= y;
+                    self.is_valid(&kicked).then_some(kicked)
+                })
+            }
+        }
+    }
+
+    pub fn place(&mut self, tetro: &Tetromino) {
+        for point in tetro.cells() {
+            self.set(point.x as usize, point.y as usize);
+        }
+    }
+
+    /// the full rows, top to bottom
+    pub fn full_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..HEIGHT).filter(|&y| self.rows[y] == FULL_ROW)
+    }
+
+    /// removes the full rows and returns how many there were
+    pub fn clear_lines(&mut self) -> u32 {
+        let mut lines = 0;
+        let mut to = HEIGHT;
+        for from in (0..HEIGHT).rev() {
+            if self.rows[from] == FULL_ROW {
+                lines += 1;
+            } else {
+                to -= 1;
+                self.rows[to] = self.rows[from];
+            }
+        }
+        self.rows[..to].fill(0);
+        lines
+    }
+
+    /// places the tetromino and clears the lines it completes, returning how many
+    pub fn lock(&mut self, tetro: &Tetromino) -> u32 {
+        self.place(tetro);
+        self.clear_lines()
+    }
+
+    pub fn column_height(&self, x: usize) -> u8 {
+        self.rows
+            .iter()
+            .position(|row| row & (1 << x) != 0)
+            .map_or(0, |y| (HEIGHT - y) as u8)
+    }
+
+    /// height of the tallest column
+    pub fn height(&self) -> u8 {
+        self.rows
+            .iter()
+            .position(|&row| row != 0)
+            .map_or(0, |y| (HEIGHT - y) as u8)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|&row| row == 0)
+    }
+}
@@ -0,0 +1,309 @@
+pub mod features;
+
+use std::{fmt::Display, ops::Deref, str::FromStr};
+
+use anyhow::bail;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tetris::{
+    bitboard::BitBoard,
+    cell::Cell,
+    rules::Kicks180,
+    tetromino::{Tetromino, tetromino_kind::TetrominoKind},
+};
+use features::{FEATURES, Features};
+
+const LINE_CLEAR_WEIGHT: f32 = 0.76;
+const HEIGHT_DIFFERENCE_WEIGHT: f32 = -0.18;
+const HEIGHT_WEIGHT: f32 = -0.51;
+const MAX_HEIGHT_WEIGHT: f32 = -0.1;
+const HOLES_WEIGHT: f32 = -0.36;
+const COVERED_WEIGHT: f32 = -0.1;
+const ROW_TRANSITIONS_WEIGHT: f32 = -0.1;
+const COLUMN_TRANSITIONS_WEIGHT: f32 = -0.3;
+const WELL_WEIGHT: f32 = -0.1;
+const T_SLOT_WEIGHT: f32 = 0.2;
+
+/// The cells with their colours, plus the same occupancy as a `BitBoard` which everything that
+/// only cares whether a cell is taken reads instead. The weights multiply the matching field of
+/// `Features` in `grade`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Board {
+    pub line_clear_weight: f32,
+    /// bumpiness
+    pub height_difference_weight: f32,
+    /// aggregate height
+    pub height_weight: f32,
+    #[serde(default)]
+    pub max_height_weight: f32,
+    pub holes_weight: f32,
+    #[serde(default)]
+    pub covered_weight: f32,
+    #[serde(default)]
+    pub row_transitions_weight: f32,
+    #[serde(default)]
+    pub column_transitions_weight: f32,
+    #[serde(default)]
+    pub well_weight: f32,
+    #[serde(default)]
+    pub t_slot_weight: f32,
+    board: [[Cell; 10]; 20],
+    bits: BitBoard,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            line_clear_weight: LINE_CLEAR_WEIGHT,
+            height_difference_weight: HEIGHT_DIFFERENCE_WEIGHT,
+            height_weight: HEIGHT_WEIGHT,
+            max_height_weight: MAX_HEIGHT_WEIGHT,
+            holes_weight: HOLES_WEIGHT,
+            covered_weight: COVERED_WEIGHT,
+            row_transitions_weight: ROW_TRANSITIONS_WEIGHT,
+            column_transitions_weight: COLUMN_TRANSITIONS_WEIGHT,
+            well_weight: WELL_WEIGHT,
+            t_slot_weight: T_SLOT_WEIGHT,
+            board: [[Cell::default(); 10]; 20],
+            bits: BitBoard::default(),
+        }
+    }
+}
+
+impl Board {
+    pub fn bits(&self) -> &BitBoard {
+        &self.bits
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        self.board[y][x] = cell;
+        if cell.is_filled() {
+            self.bits.set(x, y);
+        } else {
+            self.bits.rows[y] &= !(1 << x);
+        }
+    }
+
+    /// checks that the tetromino is inside the board and doesn't overlap anything
+    pub fn is_valid(&self, tetro: &Tetromino) -> bool {
+        self.bits.is_valid(tetro)
+    }
+
+    /// This function checks where a tetromino would hard fall to and returns the amount of y you
+    /// have to add to reach that point.
+    pub fn drop_distance(&self, tetro: &Tetromino) -> f32 {
+        self.bits.drop_distance(tetro)
+    }
+
+    /// rotates the tetromino, nudging it by a cell in each direction if it doesn't fit in place.
+    /// returns None if none of the nudges fit either
+    pub fn rotate(&self, tetro: &Tetromino, radians: f32) -> Option<Tetromino> {
+        self.bits.rotate(tetro, radians)
+    }
+
+    /// turns the tetromino half way round, see `BitBoard::rotate_180`
+    pub fn rotate_180(&self, tetro: &Tetromino, kicks: Kicks180) -> Option<Tetromino> {
+        self.bits.rotate_180(tetro, kicks)
+    }
+
+    /// writes the tetromino into the board
+    pub fn engrave(&mut self, tetro: &Tetromino) {
+        for point in tetro.cells() {
+            self.set(point.x as usize, point.y as usize, Cell::Filled(tetro.kind));
+        }
+    }
+
+    /// removes the full rows and returns how many there were
+    pub fn clear_lines(&mut self) -> u32 {
+        for i in self.bits.full_rows().collect::<Vec<_>>() {
+            let mut prev = [Cell::Empty; 10];
+            for y in 0..=i {
+                (prev, self.board[y]) = (self.board[y], prev);
+            }
+        }
+        self.bits.clear_lines()
+    }
+
+    /// engraves the tetromino and clears the lines it completes, returning how many
+    pub fn lock(&mut self, tetro: &Tetromino) -> u32 {
+        self.engrave(tetro);
+        self.clear_lines()
+    }
+
+    /// the weighted sum of the board's `Features`, higher is better
+    pub fn grade(&self, lines_cleared: f32) -> f32 {
+        self.evaluate(&self.bits, lines_cleared)
+    }
+
+    /// grades some other position with this board's weights, for searches that only keep the
+    /// occupancy of the boards they look at
+    pub fn evaluate(&self, bits: &BitBoard, lines_cleared: f32) -> f32 {
+        Features::new(bits, lines_cleared)
+            .to_array()
+            .iter()
+            .zip(self.weights())
+            .map(|(feature, weight)| feature * weight)
+            .sum()
+    }
+
+    /// the weights in the same order as `Features::to_array`
+    pub fn weights(&self) -> [f32; FEATURES] {
+        [
+            self.line_clear_weight,
+            self.height_weight,
+            self.height_difference_weight,
+            self.max_height_weight,
+            self.holes_weight,
+            self.covered_weight,
+            self.row_transitions_weight,
+            self.column_transitions_weight,
+            self.well_weight,
+            self.t_slot_weight,
+        ]
+    }
+
+    /// sets the weights from an array in the order of `Features::to_array`
+    pub fn set_weights(&mut self, weights: [f32; FEATURES]) {
+        [
+            self.line_clear_weight,
+            self.height_weight,
+            self.height_difference_weight,
+            self.max_height_weight,
+            self.holes_weight,
+            self.covered_weight,
+            self.row_transitions_weight,
+            self.column_transitions_weight,
+            self.well_weight,
+            self.t_slot_weight,
+        ] = weights;
+    }
+
+    pub fn get_holes(&self) -> f32 {
+        Features::new(&self.bits, 0.).holes
+    }
+
+    pub fn get_rows_cleared(&self) -> u8 {
+        self.bits.full_rows().count() as u8
+    }
+
+    /// height of the tallest column
+    pub fn height(&self) -> u8 {
+        self.bits.height()
+    }
+}
+
+impl Deref for Board {
+    type Target = [[Cell; 10]; 20];
+    fn deref(&self) -> &Self::Target {
+        &self.board
+    }
+}
+
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut text = String::new();
+        for row in **self {
+            for cell in row {
+                if cell.is_empty() {
+                    text += "  ";
+                } else {
+                    text += "██";
+                }
+            }
+            text += "\n";
+        }
+        write!(f, "{}", text)
+    }
+}
+
+/// Reads a board diagram, one line per row from the top. A cell is `.` or a space when empty,
+/// the piece's letter when it's part of one and `#`, `X` or `G` for garbage, letters in either
+/// case. Missing rows at the top and missing cells at the end of a row are empty, so a diagram
+/// only needs to show the stack. Diagrams with `█` or rows wider than 10 are read two characters
+/// a cell, which is what `Display` prints.
+impl FromStr for Board {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().collect();
+        let first = lines.iter().position(|row| !row.trim().is_empty());
+        let last = lines.iter().rposition(|row| !row.trim().is_empty());
+        let rows = match (first, last) {
+            (Some(first), Some(last)) => &lines[first..=last],
+            _ => &[][..],
+        };
+
+        let mut board = Self::default();
+        if rows.len() > board.len() {
+            bail!("{} rows don't fit on the board", rows.len());
+        }
+        let wide = rows
+            .iter()
+            .any(|row| row.contains('█') || row.chars().count() > 10);
+
+        let top = board.len() - rows.len();
+        for (y, row) in rows.iter().enumerate() {
+            let cells: Vec<char> = if wide {
+                row.chars().step_by(2).collect()
+            } else {
+                row.chars().collect()
+            };
+            if cells.len() > 10 {
+                bail!("row {} is wider than the board", y + 1);
+            }
+            for (x, char) in cells.into_iter().enumerate() {
+                let cell = match char {
+                    '.' | ' ' => Cell::Empty,
+                    '#' | 'X' | 'x' | 'G' | 'g' | '█' => Cell::Garbage,
+                    _ => Cell::Filled(TetrominoKind::try_from(char)?),
+                };
+                board.set(x, top + y, cell);
+            }
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_stack_from_the_bottom() {
+        let board: Board = "\n\nT.........\nTT.....OO.\nT...JJJOO#\n\n"
+            .parse()
+            .unwrap();
+        assert_eq!(board.height(), 3);
+        assert_eq!(board[17][0], Cell::Filled(TetrominoKind::T));
+        assert_eq!(board[17][1], Cell::Empty);
+        assert_eq!(board[19][6], Cell::Filled(TetrominoKind::J));
+        assert_eq!(board[19][9], Cell::Garbage);
+        assert_eq!(board.bits().rows[19], 0b1111110001);
+    }
+
+    #[test]
+    fn fills_in_short_rows() {
+        let board: Board = "x G\n#g".parse().unwrap();
+        assert_eq!(board[18][..3], [Cell::Garbage, Cell::Empty, Cell::Garbage]);
+        assert_eq!(board[19][..3], [Cell::Garbage, Cell::Garbage, Cell::Empty]);
+        assert!(board[19][2..].iter().all(Cell::is_empty));
+    }
+
+    #[test]
+    fn reads_back_what_it_prints() {
+        let board: Board = "....S.....\n...SS..ZZ.\nI..S....ZZ".parse().unwrap();
+        let printed: Board = board.to_string().parse().unwrap();
+        // the colours don't survive printing, only which cells are taken
+        assert_eq!(printed.bits(), board.bits());
+    }
+
+    #[test]
+    fn rejects_boards_that_do_not_fit() {
+        // rows wider than 10 are read two characters a cell, this is still 11 of them
+        assert!("#".repeat(21).parse::<Board>().is_err());
+        assert!("#\n".repeat(21).parse::<Board>().is_err());
+        assert!("#.Q".parse::<Board>().is_err());
+        assert!("".parse::<Board>().unwrap().bits().is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::tetris::bitboard::{BitBoard, HEIGHT, WIDTH};
+
+const FULL_ROW: u16 = (1 << WIDTH) - 1;
+
+/// how many features the evaluator looks at, and so how many weights a board has
+pub const FEATURES: usize = 10;
+
+/// names of the features in the order of `Features::to_array`
+pub const FEATURE_NAMES: [&str; FEATURES] = [
+    "lines",
+    "aggregate_height",
+    "bumpiness",
+    "max_height",
+    "holes",
+    "covered_cells",
+    "row_transitions",
+    "column_transitions",
+    "well_depth",
+    "t_slots",
+];
+
+/// The numbers a board is judged by. Each one is multiplied by its weight on `Board` and summed,
+/// so good features get positive weights and bad ones negative.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Features {
+    /// lines cleared by the move that led to the board
+    pub lines: f32,
+    /// sum of the column heights
+    pub aggregate_height: f32,
+    /// sum of the height differences between neighbouring columns
+    pub bumpiness: f32,
+    pub max_height: f32,
+    /// empty cells with something above them
+    pub holes: f32,
+    /// filled cells sitting above a hole in the same column
+    pub covered_cells: f32,
+    /// filled and empty cells next to each other in a row, the walls count as filled
+    pub row_transitions: f32,
+    /// filled and empty cells on top of each other in a column, the floor counts as filled
+    pub column_transitions: f32,
+    /// empty cells with both sides filled, deeper ones counting more (1 + 2 + .. + depth)
+    pub well_depth: f32,
+    /// places a T piece can be spun into to clear lines
+    pub t_slots: f32,
+}
+
+impl Features {
+    pub fn new(board: &BitBoard, lines: f32) -> Self {
+        let rows = &board.rows;
+        let heights: [u8; WIDTH] = std::array::from_fn(|x| board.column_height(x));
+
+        let mut features = Self {
+            lines,
+            aggregate_height: heights.iter().map(|&h| h as f32).sum(),
+            bumpiness: heights.windows(2).map(|w| w[0].abs_diff(w[1]) as f32).sum(),
+            max_height: heights.iter().copied().max().unwrap_or(0) as f32,
+            ..Default::default()
+        };
+
+        // columns with something above the current row, and how many cells that is
+        let mut covered = 0u16;
+        let mut above = [0u8; WIDTH];
+        let mut well = [0u8; WIDTH];
+
+        for (y, &row) in rows.iter().enumerate() {
+            let empty = !row & FULL_ROW;
+            // the row with a filled wall on each side, column x is bit x + 1
+            let walled = ((row as u32) << 1) | 1 | (1 << (WIDTH + 1));
+
+            let holes = empty & covered;
+            features.holes += holes.count_ones() as f32;
+            let wells = empty & walled as u16 & (walled >> 2) as u16;
+
+            for x in 0..WIDTH {
+                if holes & (1 << x) != 0 {
+                    features.covered_cells += above[x] as f32;
+                }
+                if row & (1 << x) != 0 {
+                    above[x] += 1;
+                }
+                if wells & (1 << x) != 0 {
+                    well[x] += 1;
+                    features.well_depth += well[x] as f32;
+                } else {
+                    well[x] = 0;
+                }
+            }
+            covered |= row;
+
+            // the empty rows above the stack are skipped, they'd all count the same two walls
+            if row != 0 {
+                features.row_transitions +=
+                    ((walled ^ (walled >> 1)) & ((1 << (WIDTH + 1)) - 1)).count_ones() as f32;
+            }
+            if y > 0 {
+                features.column_transitions += (row ^ rows[y - 1]).count_ones() as f32;
+            }
+        }
+        // against the floor
+        features.column_transitions += (!rows[HEIGHT - 1] & FULL_ROW).count_ones() as f32;
+
+        for y in 1..HEIGHT - 1 {
+            features.t_slots += t_slots(rows[y - 1], rows[y], rows[y + 1]).count_ones() as f32;
+        }
+
+        features
+    }
+
+    pub fn to_array(self) -> [f32; FEATURES] {
+        [
+            self.lines,
+            self.aggregate_height,
+            self.bumpiness,
+            self.max_height,
+            self.holes,
+            self.covered_cells,
+            self.row_transitions,
+            self.column_transitions,
+            self.well_depth,
+            self.t_slots,
+        ]
+    }
+}
+
+/// Columns where a T pointing down fits with its middle in `row`, the row under it is blocked
+/// apart from the stem, and there's an overhang on one of the top corners so the T has to be spun
+/// in.
+fn t_slots(over: u16, row: u16, under: u16) -> u16 {
+    let empty = !row & FULL_ROW;
+    let t_fits = empty & (empty << 1) & (empty >> 1) & !under;
+    let stem_walled = (under << 1) & (under >> 1);
+    let overhang = (over << 1) | (over >> 1);
+
+    t_fits & stem_walled & overhang
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+use super::tetromino::tetromino_kind::TetrominoKind;
+
+/// What's in a cell. Colours are up to the renderer, so skins and board analysis all work from
+/// the same state.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Cell {
+    #[default]
+    Empty,
+    /// part of a locked piece
+    Filled(TetrominoKind),
+    Garbage,
+    /// where the falling piece would land, only ever on `Tetris::get_full_board`
+    Ghost(TetrominoKind),
+    /// a taken cell modes can pick out, like the targets of a puzzle
+    Special,
+}
+
+impl Cell {
+    /// the ghost doesn't take up its cells, so it counts as empty
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Empty | Self::Ghost(_))
+    }
+
+    pub fn is_filled(&self) -> bool {
+        !self.is_empty()
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::{Duration, Instant};
+
+/// simulation ticks per second
+pub const TICK_RATE: u32 = 60;
+pub const TICK: Duration = Duration::from_nanos(1_000_000_000 / TICK_RATE as u64);
+
+/// the most ticks handed out at once, so a long stall (dragging the window, a breakpoint) doesn't
+/// make the game fast forward
+const MAX_CATCH_UP: u32 = 10;
+
+/// Turns real time into a whole number of fixed ticks, carrying the remainder over to the next
+/// call. The game only ever sees ticks, so it runs the same no matter the frame rate.
+#[derive(Debug, Default, Clone)]
+pub struct Clock {
+    last: Option<Instant>,
+    accumulator: Duration,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// returns how many ticks have passed since the last call
+    pub fn ticks(&mut self) -> u32 {
+        let now = Instant::now();
+        let last = self.last.replace(now).unwrap_or(now);
+        self.advance(now - last)
+    }
+
+    /// feeds `elapsed` into the clock by hand and returns how many ticks it made up
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed;
+        let ticks = (self.accumulator.as_nanos() / TICK.as_nanos()) as u32;
+        if ticks > MAX_CATCH_UP {
+            self.accumulator = Duration::ZERO;
+            return MAX_CATCH_UP;
+        }
+        self.accumulator -= TICK * ticks;
+        ticks
+    }
+}
@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use anyhow::{Context, bail};
+
+use super::{
+    GameView,
+    board::Board,
+    rules::Kicks180,
+    tetromino::{Tetromino, tetromino_kind::TetrominoKind},
+};
+
+/// A position written out as text, for tests and puzzles. It's a board in the format of
+/// `Board::from_str` with optional lines naming the pieces, which can go before or after it:
+///
+/// ```text
+/// piece: T
+/// hold: I
+/// queue: SZO
+/// ..........
+/// ##...#####
+/// ###.######
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Diagram {
+    pub board: Board,
+    /// the piece in play, at its spawn position
+    pub piece: Option<TetrominoKind>,
+    pub hold: Option<TetrominoKind>,
+    pub queue: Vec<TetrominoKind>,
+}
+
+impl Diagram {
+    /// what a bot would see in this position, if there's a piece in play
+    pub fn view(&self) -> Option<GameView<'_>> {
+        Some(GameView {
+            board: &self.board,
+            piece: Tetromino::from_kind(self.piece?),
+            hold: self.hold,
+            can_hold: true,
+            queue: self.queue.clone(),
+            kicks_180: Kicks180::default(),
+        })
+    }
+}
+
+impl FromStr for Diagram {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut diagram = Self::default();
+        let mut rows = String::new();
+
+        for line in s.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                rows += line;
+                rows += "\n";
+                continue;
+            };
+            let value = value.trim();
+            let kind = || {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(char), None) => TetrominoKind::try_from(char),
+                    _ => bail!("{} should be a single piece", key),
+                }
+            };
+            match key.trim() {
+                "piece" => diagram.piece = Some(kind()?),
+                "hold" => diagram.hold = Some(kind()?),
+                "queue" => {
+                    diagram.queue = value
+                        .chars()
+                        .filter(|char| !char.is_whitespace())
+                        .map(TetrominoKind::try_from)
+                        .collect::<anyhow::Result<_>>()?
+                }
+                key => bail!("unknown line {}", key),
+            }
+        }
+
+        diagram.board = rows.parse().context("invalid board")?;
+        Ok(diagram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TetrominoKind::*;
+
+    #[test]
+    fn reads_pieces_around_the_board() {
+        let diagram: Diagram = "piece: T\nhold: I\n##...#####\n###.######\nqueue: S Z O"
+            .parse()
+            .unwrap();
+        assert_eq!(diagram.piece, Some(T));
+        assert_eq!(diagram.hold, Some(I));
+        assert_eq!(diagram.queue, vec![S, Z, O]);
+        assert_eq!(diagram.board.height(), 2);
+        assert_eq!(diagram.board.bits().rows[19], 0b1111110111);
+
+        let view = diagram.view().unwrap();
+        assert_eq!(view.piece.kind, T);
+        assert!(view.can_hold);
+    }
+
+    #[test]
+    fn only_a_board() {
+        let diagram: Diagram = "#########.".parse().unwrap();
+        assert!(diagram.piece.is_none() && diagram.hold.is_none() && diagram.queue.is_empty());
+        assert!(diagram.view().is_none());
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        assert!("piece: TI".parse::<Diagram>().is_err());
+        assert!("piece: X".parse::<Diagram>().is_err());
+        assert!("next: T".parse::<Diagram>().is_err());
+        assert!("queue: TIQ".parse::<Diagram>().is_err());
+    }
+}
@@ -0,0 +1,14 @@
+/// Things that happened in a game, for whoever is driving it to react to. They pile up until
+/// `Tetris::drain_events` is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// a piece locked and cleared `lines`, `t_spin` if it was a T rotated into a slot with three
+    /// of its corners filled
+    Lock {
+        lines: u32,
+        perfect_clear: bool,
+        t_spin: bool,
+    },
+    /// the game topped out with `lines` cleared and `score` points and started over
+    GameOver { lines: u32, score: u32 },
+}
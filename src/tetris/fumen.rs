@@ -0,0 +1,735 @@
+//! Import and export of boards as fumen (v115) strings, the format the community shares setups
+//! in. A fumen is a list of pages, each with a field, optionally a piece and a comment; the field
+//! of each page is stored as the difference from the page before once its piece has locked.
+
+use anyhow::{Context, bail};
+
+use super::{
+    board::Board,
+    cell::Cell,
+    point::Point,
+    tetromino::{Tetromino, tetromino_kind::TetrominoKind},
+};
+
+const PREFIX: &str = "v115@";
+const ENCODE_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const COMMENT_TABLE: &[u8; 95] = b" !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+/// base comment characters are packed in
+const COMMENT_BASE: u32 = COMMENT_TABLE.len() as u32 + 1;
+
+const FIELD_WIDTH: usize = 10;
+/// rows above the garbage row, the board's 20 are the bottom ones
+const FIELD_TOP: usize = 23;
+/// cells in a field including the garbage row under it
+const FIELD_BLOCKS: usize = (FIELD_TOP + 1) * FIELD_WIDTH;
+
+/// rotation numbers in a fumen
+const REVERSE: u32 = 0;
+const RIGHT: u32 = 1;
+const SPAWN: u32 = 2;
+const LEFT: u32 = 3;
+
+/// One page of a fumen.
+#[derive(Debug, Clone, Default)]
+pub struct Page {
+    pub board: Board,
+    /// the piece shown on the page, where it is
+    pub piece: Option<Tetromino>,
+    pub comment: String,
+    /// whether the piece locks into the board for the next page
+    pub lock: bool,
+}
+
+/// a fumen field, row 0 at the top and the garbage row last, cells numbered like fumen does
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Field([u8; FIELD_BLOCKS]);
+
+impl Field {
+    fn from_board(board: &Board) -> Self {
+        let mut field = [0; FIELD_BLOCKS];
+        let offset = (FIELD_TOP - board.len()) * FIELD_WIDTH;
+        for (y, row) in board.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                field[offset + y * FIELD_WIDTH + x] = cell_number(*cell);
+            }
+        }
+        Self(field)
+    }
+
+    fn to_board(self) -> anyhow::Result<Board> {
+        let mut board = Board::default();
+        let offset = (FIELD_TOP - board.len()) * FIELD_WIDTH;
+        if self.0[..offset].iter().any(|&x| x != 0) {
+            bail!("the field is taller than the board");
+        }
+        for y in 0..board.len() {
+            for x in 0..FIELD_WIDTH {
+                board.set(x, y, number_cell(self.0[offset + y * FIELD_WIDTH + x]));
+            }
+        }
+        Ok(board)
+    }
+
+    /// The field the next page starts from. Only a page that locks changes it: its piece goes in,
+    /// full rows are cleared, then the garbage row rises and the field is mirrored if asked to.
+    fn after(mut self, action: &Action) -> Self {
+        if !action.lock {
+            return self;
+        }
+        if let Some(piece) = &action.piece {
+            for (x, y) in piece.blocks() {
+                self.0[index(x, y)] = piece.kind;
+            }
+        }
+        self.clear_lines();
+        if action.rise {
+            self.rise();
+        }
+        if action.mirror {
+            self.mirror();
+        }
+        self
+    }
+
+    /// the garbage row never clears
+    fn clear_lines(&mut self) {
+        let rows: Vec<[u8; FIELD_WIDTH]> = self.0[..FIELD_TOP * FIELD_WIDTH]
+            .chunks(FIELD_WIDTH)
+            .filter(|row| row.contains(&0))
+            .map(|row| row.try_into().unwrap())
+            .collect();
+        let cleared = FIELD_TOP - rows.len();
+        self.0[..FIELD_TOP * FIELD_WIDTH].fill(0);
+        for (i, row) in rows.iter().enumerate() {
+            let start = (cleared + i) * FIELD_WIDTH;
+            self.0[start..start + FIELD_WIDTH].copy_from_slice(row);
+        }
+    }
+
+    /// pushes the garbage row up into the field
+    fn rise(&mut self) {
+        self.0.copy_within(FIELD_WIDTH.., 0);
+        self.0[FIELD_BLOCKS - FIELD_WIDTH..].fill(0);
+    }
+
+    fn mirror(&mut self) {
+        for row in self.0[..FIELD_TOP * FIELD_WIDTH].chunks_mut(FIELD_WIDTH) {
+            row.reverse();
+        }
+    }
+}
+
+/// index of a cell given fumen coordinates, where y counts up from the bottom row and the
+/// garbage row is -1
+fn index(x: i32, y: i32) -> usize {
+    (FIELD_TOP as i32 - y - 1) as usize * FIELD_WIDTH + x as usize
+}
+
+fn cell_number(cell: Cell) -> u8 {
+    match cell {
+        Cell::Empty | Cell::Ghost(_) => 0,
+        Cell::Filled(kind) => kind_number(kind),
+        Cell::Garbage | Cell::Special => 8,
+    }
+}
+
+fn number_cell(number: u8) -> Cell {
+    match number {
+        0 => Cell::Empty,
+        1..=7 => Cell::Filled(number_kind(number)),
+        _ => Cell::Garbage,
+    }
+}
+
+fn kind_number(kind: TetrominoKind) -> u8 {
+    match kind {
+        TetrominoKind::I => 1,
+        TetrominoKind::L => 2,
+        TetrominoKind::O => 3,
+        TetrominoKind::Z => 4,
+        TetrominoKind::T => 5,
+        TetrominoKind::J => 6,
+        TetrominoKind::S => 7,
+    }
+}
+
+fn number_kind(number: u8) -> TetrominoKind {
+    match number {
+        1 => TetrominoKind::I,
+        2 => TetrominoKind::L,
+        3 => TetrominoKind::O,
+        4 => TetrominoKind::Z,
+        5 => TetrominoKind::T,
+        6 => TetrominoKind::J,
+        _ => TetrominoKind::S,
+    }
+}
+
+/// a piece the way fumen describes it: a kind number, a rotation and the rotation centre, in
+/// fumen coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FumenPiece {
+    kind: u8,
+    rotation: u32,
+    x: i32,
+    y: i32,
+}
+
+impl FumenPiece {
+    fn blocks(&self) -> [(i32, i32); 4] {
+        let spawn = match self.kind {
+            1 => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+            2 => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+            3 => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            4 => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+            5 => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+            6 => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+            _ => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+        };
+        spawn.map(|(x, y)| {
+            let (x, y) = match self.rotation {
+                SPAWN => (x, y),
+                RIGHT => (y, -x),
+                REVERSE => (-x, -y),
+                _ => (-y, x),
+            };
+            (self.x + x, self.y + y)
+        })
+    }
+
+    /// the old fumen coordinates are off by one for some pieces and rotations, this is how far
+    fn shift(&self) -> (i32, i32) {
+        match (self.kind, self.rotation) {
+            (3, LEFT) => (1, -1),
+            (3, REVERSE) => (1, 0),
+            (3, SPAWN) => (0, -1),
+            (1, REVERSE) => (1, 0),
+            (1, LEFT) => (0, -1),
+            (7, SPAWN) => (0, -1),
+            (7, RIGHT) => (-1, 0),
+            (4, SPAWN) => (0, -1),
+            (4, LEFT) => (1, 0),
+            _ => (0, 0),
+        }
+    }
+
+    fn from_tetromino(tetro: &Tetromino, height: usize) -> Self {
+        let cells = fumen_cells(tetro, height);
+        let kind = kind_number(tetro.kind);
+        for rotation in [SPAWN, RIGHT, REVERSE, LEFT] {
+            for &(x, y) in &cells {
+                let piece = Self {
+                    kind,
+                    rotation,
+                    x,
+                    y,
+                };
+                if same_cells(piece.blocks(), cells) {
+                    return piece;
+                }
+            }
+        }
+        unreachable!("every tetromino matches one of its own rotations")
+    }
+
+    fn to_tetromino(self, height: usize) -> anyhow::Result<Tetromino> {
+        let cells = self.blocks();
+        let spawn = Tetromino::from_kind(number_kind(self.kind));
+        for rotation in 0..4 {
+            let mut tetro = spawn;
+            tetro.rotate((rotation as f32 * 90.).to_radians());
+            let first = min_cell(&fumen_cells(&tetro, height));
+            let target = min_cell(&cells);
+            tetro.anchor.x += (target.0 - first.0) as f32;
+            tetro.anchor.y -= (target.1 - first.1) as f32;
+            if same_cells(fumen_cells(&tetro, height), cells) {
+                return Ok(tetro);
+            }
+        }
+        bail!("piece doesn't fit on the board")
+    }
+}
+
+/// the tetromino's cells in fumen coordinates, which go on above the board
+fn fumen_cells(tetro: &Tetromino, height: usize) -> [(i32, i32); 4] {
+    tetro.points.map(|Point { x, y }| {
+        let x = (x + tetro.anchor.x).floor() as i32;
+        let y = (y + tetro.anchor.y).floor() as i32;
+        (x, height as i32 - 1 - y)
+    })
+}
+
+fn min_cell(cells: &[(i32, i32); 4]) -> (i32, i32) {
+    *cells.iter().min().unwrap()
+}
+
+fn same_cells(mut a: [(i32, i32); 4], mut b: [(i32, i32); 4]) -> bool {
+    a.sort();
+    b.sort();
+    a == b
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Action {
+    piece: Option<FumenPiece>,
+    rise: bool,
+    mirror: bool,
+    colorize: bool,
+    comment: bool,
+    lock: bool,
+}
+
+impl Action {
+    fn encode(&self) -> u32 {
+        let (kind, rotation, position) = match self.piece {
+            Some(piece) => {
+                let (dx, dy) = piece.shift();
+                let position = index(piece.x - dx, piece.y - dy) as u32;
+                (piece.kind as u32, piece.rotation, position)
+            }
+            None => (0, REVERSE, 0),
+        };
+
+        let mut value = !self.lock as u32;
+        for flag in [self.comment, self.colorize, self.mirror, self.rise] {
+            value = value * 2 + flag as u32;
+        }
+        value = value * FIELD_BLOCKS as u32 + position;
+        value = value * 4 + rotation;
+        value * 8 + kind
+    }
+
+    fn decode(mut value: u32) -> anyhow::Result<Self> {
+        let mut take = |base: u32| {
+            let x = value % base;
+            value /= base;
+            x
+        };
+        let kind = take(8) as u8;
+        let rotation = take(4);
+        let position = take(FIELD_BLOCKS as u32) as i32;
+        let rise = take(2) == 1;
+        let mirror = take(2) == 1;
+        let colorize = take(2) == 1;
+        let comment = take(2) == 1;
+        let lock = take(2) == 0;
+
+        let piece = (kind != 0).then(|| {
+            let mut piece = FumenPiece {
+                kind,
+                rotation,
+                x: position % FIELD_WIDTH as i32,
+                y: FIELD_TOP as i32 - position / FIELD_WIDTH as i32 - 1,
+            };
+            let (dx, dy) = piece.shift();
+            piece.x += dx;
+            piece.y += dy;
+            piece
+        });
+        if let Some(piece) = piece {
+            let inside = |(x, y): (i32, i32)| {
+                (0..FIELD_WIDTH as i32).contains(&x) && (0..FIELD_TOP as i32).contains(&y)
+            };
+            if !piece.blocks().into_iter().all(inside) {
+                bail!("piece is outside the field");
+            }
+        }
+
+        Ok(Self {
+            piece,
+            rise,
+            mirror,
+            colorize,
+            comment,
+            lock,
+        })
+    }
+}
+
+/// the fumen alphabet packed in little endian chunks
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn push(&mut self, mut value: u32, len: usize) {
+        for _ in 0..len {
+            self.0.push(ENCODE_TABLE[(value % 64) as usize]);
+            value /= 64;
+        }
+    }
+}
+
+struct Reader<'a>(std::slice::Iter<'a, u8>);
+
+impl Reader<'_> {
+    fn poll(&mut self, len: usize) -> anyhow::Result<u32> {
+        let mut value = 0;
+        for i in 0..len {
+            let char = *self.0.next().context("fumen ended early")?;
+            let digit = ENCODE_TABLE
+                .iter()
+                .position(|&x| x == char)
+                .with_context(|| format!("invalid fumen character {}", char as char))?;
+            value += digit as u32 * 64u32.pow(i as u32);
+        }
+        Ok(value)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.len() == 0
+    }
+}
+
+/// Turns pages into a fumen string. The field of every page is stored relative to the page
+/// before it, after that page's piece has locked.
+pub fn encode(pages: &[Page]) -> String {
+    let mut writer = Writer::default();
+    let mut prev = Field([0; FIELD_BLOCKS]);
+    let mut prev_comment = "";
+    // where the count of unchanged pages following an unchanged field goes
+    let mut repeat: Option<usize> = None;
+
+    for (i, page) in pages.iter().enumerate() {
+        let height = page.board.len();
+        let field = Field::from_board(&page.board);
+
+        let changed = encode_field(&mut writer, &prev, &field);
+        match repeat {
+            _ if changed => repeat = None,
+            Some(at) if writer.0[at] != ENCODE_TABLE[63] => {
+                let count = ENCODE_TABLE
+                    .iter()
+                    .position(|&x| x == writer.0[at])
+                    .unwrap();
+                writer.0.truncate(writer.0.len() - 2);
+                writer.0[at] = ENCODE_TABLE[count + 1];
+            }
+            _ => {
+                writer.push(0, 1);
+                repeat = Some(writer.0.len() - 1);
+            }
+        }
+
+        let piece = page
+            .piece
+            .map(|tetro| FumenPiece::from_tetromino(&tetro, height));
+        let comment = page.comment != prev_comment;
+        let action = Action {
+            piece,
+            colorize: i == 0,
+            comment,
+            lock: page.lock,
+            ..Default::default()
+        };
+        writer.push(action.encode(), 3);
+
+        if comment {
+            encode_comment(&mut writer, &page.comment);
+            prev_comment = &page.comment;
+        }
+
+        prev = field.after(&action);
+    }
+
+    let data = String::from_utf8(writer.0).expect("the fumen alphabet is ascii");
+    // fumen breaks the data up with a `?` after the first 42 characters and every 47 after that
+    let mut text = String::from(PREFIX);
+    let mut rest = data.as_str();
+    let mut chunk = 42;
+    while rest.len() > chunk {
+        text += &rest[..chunk];
+        text += "?";
+        rest = &rest[chunk..];
+        chunk = 47;
+    }
+    text += rest;
+    text
+}
+
+/// writes the difference between the fields run length encoded, returns false if there was none
+fn encode_field(writer: &mut Writer, prev: &Field, field: &Field) -> bool {
+    let mut runs: Vec<(u8, usize)> = vec![];
+    for (before, after) in prev.0.iter().zip(field.0) {
+        let diff = after + 8 - before;
+        match runs.last_mut() {
+            Some((last, count)) if *last == diff => *count += 1,
+            _ => runs.push((diff, 1)),
+        }
+    }
+
+    for &(diff, count) in &runs {
+        writer.push(diff as u32 * FIELD_BLOCKS as u32 + count as u32 - 1, 2);
+    }
+    runs != [(8, FIELD_BLOCKS)]
+}
+
+fn encode_comment(writer: &mut Writer, comment: &str) {
+    let escaped: Vec<u32> = escape(comment)
+        .bytes()
+        .map(|x| COMMENT_TABLE.iter().position(|&c| c == x).unwrap_or(0) as u32)
+        .collect();
+    writer.push(escaped.len() as u32, 2);
+    for chunk in escaped.chunks(4) {
+        let value = chunk
+            .iter()
+            .rev()
+            .fold(0, |value, &x| value * COMMENT_BASE + x);
+        writer.push(value, 5);
+    }
+}
+
+/// Reads a fumen string back into pages.
+pub fn decode(text: &str) -> anyhow::Result<Vec<Page>> {
+    let text = text.trim();
+    let data = text
+        .strip_prefix(PREFIX)
+        .or_else(|| text.strip_prefix("V115@"))
+        .context("only v115 fumen is supported")?;
+    let data: Vec<u8> = data.bytes().filter(|&x| x != b'?').collect();
+    let mut reader = Reader(data.iter());
+
+    let mut pages = vec![];
+    let mut prev = Field([0; FIELD_BLOCKS]);
+    let mut comment = String::new();
+    let mut repeat = 0;
+    let height = Board::default().len();
+
+    while !reader.is_empty() {
+        let mut field = prev;
+        if repeat > 0 {
+            repeat -= 1;
+        } else if !decode_field(&mut reader, &mut field)? {
+            repeat = reader.poll(1)?;
+        }
+
+        let action = Action::decode(reader.poll(3)?)?;
+        if action.comment {
+            comment = decode_comment(&mut reader)?;
+        }
+
+        let piece = match action.piece {
+            Some(piece) => Some(piece.to_tetromino(height)?),
+            None => None,
+        };
+        pages.push(Page {
+            board: field.to_board()?,
+            piece,
+            comment: comment.clone(),
+            lock: action.lock,
+        });
+
+        prev = field.after(&action);
+    }
+
+    Ok(pages)
+}
+
+/// applies the run length encoded difference to the field, returns false if there was none
+fn decode_field(reader: &mut Reader, field: &mut Field) -> anyhow::Result<bool> {
+    let mut index = 0;
+    let mut changed = true;
+    while index < FIELD_BLOCKS {
+        let value = reader.poll(2)? as usize;
+        let diff = value / FIELD_BLOCKS;
+        let count = value % FIELD_BLOCKS + 1;
+        if diff == 8 && count == FIELD_BLOCKS {
+            changed = false;
+        }
+        if index + count > FIELD_BLOCKS || diff > 16 {
+            bail!("invalid fumen field");
+        }
+        for cell in &mut field.0[index..index + count] {
+            *cell = (*cell as usize + diff)
+                .checked_sub(8)
+                .filter(|&x| x <= 8)
+                .context("invalid fumen field")? as u8;
+        }
+        index += count;
+    }
+    Ok(changed)
+}
+
+fn decode_comment(reader: &mut Reader) -> anyhow::Result<String> {
+    let len = reader.poll(2)? as usize;
+    let mut escaped = String::with_capacity(len);
+    while escaped.len() < len {
+        let mut value = reader.poll(5)?;
+        for _ in 0..4.min(len - escaped.len()) {
+            let char = COMMENT_TABLE
+                .get((value % COMMENT_BASE) as usize)
+                .context("invalid fumen comment")?;
+            escaped.push(*char as char);
+            value /= COMMENT_BASE;
+        }
+    }
+    Ok(unescape(&escaped))
+}
+
+/// javascript's `escape`, which fumen runs comments through
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for char in text.chars() {
+        match char {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '@' | '*' | '_' | '+' | '-' | '.' | '/' => {
+                escaped.push(char)
+            }
+            _ => {
+                let mut units = [0; 2];
+                for unit in char.encode_utf16(&mut units) {
+                    if *unit < 256 {
+                        escaped += &format!("%{:02X}", unit);
+                    } else {
+                        escaped += &format!("%u{:04X}", unit);
+                    }
+                }
+            }
+        }
+    }
+    escaped
+}
+
+/// javascript's `unescape`, anything that isn't a valid escape is kept as it is
+fn unescape(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut units = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let hex = |from: usize, len: usize| {
+            let digits: String = chars.get(from..from + len)?.iter().collect();
+            u16::from_str_radix(&digits, 16).ok()
+        };
+        match chars[i] {
+            '%' if chars.get(i + 1) == Some(&'u') && hex(i + 2, 4).is_some() => {
+                units.push(hex(i + 2, 4).unwrap());
+                i += 6;
+            }
+            '%' if hex(i + 1, 2).is_some() => {
+                units.push(hex(i + 1, 2).unwrap());
+                i += 3;
+            }
+            char => {
+                let mut buffer = [0; 2];
+                units.extend_from_slice(char.encode_utf16(&mut buffer));
+                i += 1;
+            }
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the page's piece as sorted (x, y) board cells
+    fn cells(page: &Page) -> Option<Vec<(isize, isize)>> {
+        let mut cells: Vec<_> = page.piece?.cells().iter().map(|p| (p.x, p.y)).collect();
+        cells.sort();
+        Some(cells)
+    }
+
+    fn assert_same(a: &[Page], b: &[Page]) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b) {
+            assert_eq!(a.board, b.board);
+            assert_eq!(cells(a), cells(b));
+            assert_eq!(a.comment, b.comment);
+            assert_eq!(a.lock, b.lock);
+        }
+    }
+
+    #[test]
+    fn empty_page() {
+        let page = Page {
+            lock: true,
+            ..Default::default()
+        };
+        assert_eq!(encode(&[page]), "v115@vhAAgH");
+
+        let pages = decode("v115@vhAAgH").unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].board, Board::default());
+        assert!(pages[0].piece.is_none() && pages[0].lock);
+    }
+
+    #[test]
+    fn piece_on_the_floor() {
+        let pages = decode("v115@vhAVQJ").unwrap();
+        // a T pointing up on the bottom row, centred on the fifth column
+        assert_eq!(
+            cells(&pages[0]),
+            Some(vec![(3, 19), (4, 18), (4, 19), (5, 19)])
+        );
+        assert_eq!(encode(&pages), "v115@vhAVQJ");
+    }
+
+    #[test]
+    fn pages_with_pieces_and_comments() {
+        let text = "v115@vhCVQYPAUE7QEJG98AwZk2AFrvAARmBAAPEAkXprD";
+        let pages = decode(text).unwrap();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(encode(&pages), text);
+
+        assert_eq!(pages[0].comment, "T-spin 100%");
+        // each page starts from the last one's locked piece
+        assert_eq!(pages[1].board.height(), 2);
+        assert_eq!(
+            cells(&pages[1]),
+            Some(vec![(3, 17), (4, 17), (5, 17), (6, 17)])
+        );
+        assert_eq!(pages[2].comment, "done");
+        assert_eq!(pages[2].board.height(), 3);
+    }
+
+    #[test]
+    fn piece_off_the_field() {
+        // an I lying flat against the left wall, with a block past it
+        assert!(decode("v115@vhARuB").is_err());
+    }
+
+    #[test]
+    fn round_trips_a_game() {
+        let board: Board = "T.........\nTT.....OO.\nT...JJJOO.\n".parse().unwrap();
+        let mut piece = Tetromino::from_kind(TetrominoKind::I);
+        piece.anchor.y += 15.;
+        let pages = vec![
+            Page {
+                board,
+                piece: Some(piece),
+                comment: "I ☆ 100%".into(),
+                lock: true,
+            },
+            Page {
+                piece: Some(Tetromino::from_kind(TetrominoKind::S)),
+                comment: "next".into(),
+                lock: false,
+                ..Default::default()
+            },
+        ];
+        assert_same(&decode(&encode(&pages)).unwrap(), &pages);
+    }
+
+    #[test]
+    fn only_locking_pages_rise_mirror_and_clear() {
+        let mut field = Field([0; FIELD_BLOCKS]);
+        // a full bottom row and a garbage row under it
+        field.0[FIELD_BLOCKS - 2 * FIELD_WIDTH..FIELD_BLOCKS - FIELD_WIDTH].fill(8);
+        field.0[FIELD_BLOCKS - FIELD_WIDTH] = 8;
+        let action = Action {
+            rise: true,
+            mirror: true,
+            lock: false,
+            ..Default::default()
+        };
+        assert_eq!(field.after(&action), field);
+
+        // the full row clears without a piece, then the garbage comes up and is mirrored
+        let after = field.after(&Action {
+            lock: true,
+            ..action
+        });
+        let mut expected = Field([0; FIELD_BLOCKS]);
+        expected.0[FIELD_BLOCKS - FIELD_WIDTH - 1] = 8;
+        assert_eq!(after, expected);
+    }
+}
@@ -0,0 +1,99 @@
+use super::{action::Action, bitboard::WIDTH, rules::Rules};
+
+/// Turns held left and right inputs into moves: one straight away, then after `Rules::das` ticks
+/// one every `Rules::arr` ticks, or all the way to the wall when `arr` is 0. While both
+/// directions are held the one pressed last wins.
+#[derive(Debug, Clone, Default)]
+pub struct Handling {
+    /// held directions, the newest last
+    held: Vec<i32>,
+    timer: u32,
+}
+
+impl Handling {
+    /// returns the move to play for the press
+    pub fn press(&mut self, direction: i32) -> Action {
+        self.held.retain(|&x| x != direction);
+        self.held.push(direction);
+        self.timer = 0;
+        Action::Move(direction)
+    }
+
+    pub fn release(&mut self, direction: i32) {
+        if self.held.last() == Some(&direction) {
+            self.timer = 0;
+        }
+        self.held.retain(|&x| x != direction);
+    }
+
+    /// the moves to play this tick
+    pub fn tick(&mut self, rules: &Rules) -> Vec<Action> {
+        let Some(&direction) = self.held.last() else {
+            return vec![];
+        };
+        self.timer += 1;
+        if self.timer < rules.das {
+            return vec![];
+        }
+
+        match rules.arr {
+            0 => vec![Action::Move(direction); WIDTH],
+            arr if (self.timer - rules.das).is_multiple_of(arr) => vec![Action::Move(direction)],
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the moves played on each of the next `ticks` ticks
+    fn ticks(handling: &mut Handling, rules: &Rules, ticks: usize) -> Vec<usize> {
+        (0..ticks).map(|_| handling.tick(rules).len()).collect()
+    }
+
+    #[test]
+    fn slides_after_das_every_arr() {
+        let rules = Rules {
+            das: 3,
+            arr: 2,
+            ..Rules::default()
+        };
+        let mut handling = Handling::default();
+        assert_eq!(handling.press(1), Action::Move(1));
+        assert_eq!(ticks(&mut handling, &rules, 7), [0, 0, 1, 0, 1, 0, 1]);
+
+        handling.release(1);
+        assert_eq!(ticks(&mut handling, &rules, 3), [0, 0, 0]);
+    }
+
+    #[test]
+    fn zero_arr_goes_to_the_wall() {
+        let rules = Rules {
+            das: 1,
+            arr: 0,
+            ..Rules::default()
+        };
+        let mut handling = Handling::default();
+        handling.press(-1);
+        assert_eq!(handling.tick(&rules), vec![Action::Move(-1); WIDTH]);
+    }
+
+    #[test]
+    fn last_direction_pressed_wins() {
+        let rules = Rules {
+            das: 1,
+            arr: 1,
+            ..Rules::default()
+        };
+        let mut handling = Handling::default();
+        handling.press(-1);
+        handling.press(1);
+        assert_eq!(handling.tick(&rules), [Action::Move(1)]);
+
+        // letting go of the newer one goes back to the older, charging das again
+        handling.release(1);
+        assert_eq!(handling.tick(&rules), [Action::Move(-1)]);
+    }
+}
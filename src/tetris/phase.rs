@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// Where the game is between one piece locking and the next spawning, see `Rules::are` and
+/// `Rules::line_clear_delay`. The counts are the ticks left.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /// a piece is in play
+    #[default]
+    Falling,
+    /// the full rows are still on the board, for the renderer to show them going
+    LineClear(u32),
+    /// the entry delay before the next piece spawns
+    Are(u32),
+}
@@ -1,6 +1,8 @@
 use std::ops::Add;
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Point<T: Add<Output = T> + Copy> {
     pub x: T,
     pub y: T,
@@ -0,0 +1,285 @@
+//! Puzzles: a starting position, the pieces to solve it with and a goal. They're written as
+//! diagrams (see `Diagram`) with a `goal:` line and an optional `name:`, and packs put several in
+//! one file separated by `---` lines:
+//!
+//! ```text
+//! name: first t-spin
+//! goal: t-spin double
+//! piece: T
+//! ....###...
+//! ##...#####
+//! ###.######
+//! ---
+//! goal: lines 2
+//! piece: I
+//! queue: O
+//! ...
+//! ```
+
+use std::{fs, path::Path, str::FromStr};
+
+use anyhow::{Context, bail};
+
+use super::{Tetris, diagram::Diagram, event::Event, tetromino::tetromino_kind::TetrominoKind};
+
+/// What has to happen for a puzzle to count as solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// clear this many lines in total
+    Lines(u32),
+    /// clear two lines with a T-spin
+    TSpinDouble,
+    /// leave the board empty
+    PerfectClear,
+    /// lock this many pieces without topping out
+    Survive(u32),
+}
+
+impl FromStr for Goal {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let count = |count: &str| count.parse::<u32>().context("invalid count");
+        Ok(match words[..] {
+            ["lines", lines] => Self::Lines(count(lines)?),
+            ["t-spin", "double"] | ["tsd"] => Self::TSpinDouble,
+            ["perfect", "clear"] | ["pc"] => Self::PerfectClear,
+            ["survive", pieces] => Self::Survive(count(pieces)?),
+            _ => bail!("unknown goal {}", s),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Solved,
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub name: String,
+    pub goal: Goal,
+    /// the board, the piece to start with, the ones after it and hold
+    pub diagram: Diagram,
+}
+
+impl Puzzle {
+    /// the pieces the puzzle hands out, in order
+    pub fn pieces(&self) -> Vec<TetrominoKind> {
+        self.diagram
+            .piece
+            .iter()
+            .chain(&self.diagram.queue)
+            .copied()
+            .collect()
+    }
+
+    /// a game set up at the start of the puzzle, random pieces follow the puzzle's own
+    pub fn start(&self) -> Tetris {
+        Tetris::with_pieces(self.diagram.board, &self.pieces(), self.diagram.hold)
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut name = String::new();
+        let mut goal = None;
+        let mut diagram = String::new();
+        for line in s.lines() {
+            match line.split_once(':') {
+                Some(("name", value)) => name = value.trim().to_string(),
+                Some(("goal", value)) => goal = Some(value.trim().parse()?),
+                _ => {
+                    diagram += line;
+                    diagram += "\n";
+                }
+            }
+        }
+
+        let puzzle = Self {
+            name,
+            goal: goal.context("puzzle has no goal")?,
+            diagram: diagram.parse()?,
+        };
+        if puzzle.diagram.piece.is_none() {
+            bail!("puzzle has no piece");
+        }
+        Ok(puzzle)
+    }
+}
+
+/// How far into a puzzle a game is, fed the game's events as they come.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Attempt {
+    pieces: u32,
+    lines: u32,
+}
+
+impl Attempt {
+    /// Counts the event towards the goal. Puzzles fail when the game tops out or when the pieces
+    /// run out before the goal is met, including the one in hold. Surviving is the only goal
+    /// random pieces count towards.
+    pub fn update(&mut self, puzzle: &Puzzle, event: Event) -> Status {
+        let Event::Lock {
+            lines,
+            perfect_clear,
+            t_spin,
+        } = event
+        else {
+            return Status::Failed;
+        };
+        self.pieces += 1;
+        self.lines += lines;
+
+        let solved = match puzzle.goal {
+            Goal::Lines(goal) => self.lines >= goal,
+            Goal::TSpinDouble => t_spin && lines == 2,
+            Goal::PerfectClear => perfect_clear,
+            Goal::Survive(goal) => self.pieces >= goal,
+        };
+        if solved {
+            Status::Solved
+        } else if self.pieces as usize
+            >= puzzle.pieces().len() + puzzle.diagram.hold.is_some() as usize
+            && !matches!(puzzle.goal, Goal::Survive(_))
+        {
+            Status::Failed
+        } else {
+            Status::Playing
+        }
+    }
+}
+
+/// A list of puzzles played one after the other.
+#[derive(Debug, Clone)]
+pub struct Pack {
+    puzzles: Vec<Puzzle>,
+    index: usize,
+    attempt: Attempt,
+}
+
+impl Pack {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn current(&self) -> &Puzzle {
+        &self.puzzles[self.index]
+    }
+
+    /// which puzzle this is, counting from 1, and how many there are
+    pub fn progress(&self) -> (usize, usize) {
+        (self.index + 1, self.puzzles.len())
+    }
+
+    /// counts an event from the game towards the current puzzle
+    pub fn update(&mut self, event: Event) -> Status {
+        self.attempt.update(&self.puzzles[self.index], event)
+    }
+
+    /// starts the current puzzle over
+    pub fn restart(&mut self) -> Tetris {
+        self.attempt = Attempt::default();
+        self.current().start()
+    }
+
+    /// moves on to the next puzzle, wrapping around after the last one
+    pub fn next_puzzle(&mut self) -> Tetris {
+        self.index = (self.index + 1) % self.puzzles.len();
+        self.restart()
+    }
+
+    pub fn previous_puzzle(&mut self) -> Tetris {
+        self.index = (self.index + self.puzzles.len() - 1) % self.puzzles.len();
+        self.restart()
+    }
+}
+
+impl FromStr for Pack {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut puzzles = vec![];
+        let mut text = String::new();
+        // one more separator so the last puzzle is pushed too
+        for line in s.lines().chain(["---"]) {
+            if line.trim() != "---" {
+                text += line;
+                text += "\n";
+                continue;
+            }
+            if !text.trim().is_empty() {
+                let puzzle = text
+                    .parse()
+                    .with_context(|| format!("puzzle {}", puzzles.len() + 1))?;
+                puzzles.push(puzzle);
+            }
+            text.clear();
+        }
+
+        if puzzles.is_empty() {
+            bail!("no puzzles in the pack");
+        }
+        Ok(Self {
+            puzzles,
+            index: 0,
+            attempt: Attempt::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: Event = Event::Lock {
+        lines: 0,
+        perfect_clear: false,
+        t_spin: false,
+    };
+
+    fn puzzle(text: &str) -> Puzzle {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn fails_once_the_pieces_run_out() {
+        let puzzle = puzzle("goal: perfect clear\npiece: I\nqueue: O\n####..####");
+        let mut attempt = Attempt::default();
+        assert_eq!(attempt.update(&puzzle, LOCK), Status::Playing);
+        assert_eq!(attempt.update(&puzzle, LOCK), Status::Failed);
+    }
+
+    #[test]
+    fn counts_the_piece_in_hold() {
+        let puzzle = puzzle("goal: perfect clear\npiece: O\nhold: I\n####..####");
+        let mut attempt = Attempt::default();
+        assert_eq!(attempt.update(&puzzle, LOCK), Status::Playing);
+        assert_eq!(attempt.update(&puzzle, LOCK), Status::Failed);
+    }
+
+    #[test]
+    fn solves_on_the_last_piece() {
+        let puzzle = puzzle("goal: lines 2\npiece: O\nhold: I");
+        let mut attempt = Attempt::default();
+        assert_eq!(attempt.update(&puzzle, LOCK), Status::Playing);
+        let clear = Event::Lock {
+            lines: 2,
+            perfect_clear: true,
+            t_spin: false,
+        };
+        assert_eq!(attempt.update(&puzzle, clear), Status::Solved);
+    }
+
+    #[test]
+    fn reads_the_shipped_pack() {
+        let pack = Pack::load("puzzles/basics.txt").unwrap();
+        assert!(pack.progress().1 >= 3);
+        assert_eq!(pack.current().goal, Goal::Lines(4));
+    }
+}
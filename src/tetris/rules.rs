@@ -0,0 +1,359 @@
+//! The rules a game is played by, loaded from a TOML file at startup. A file picks one of the
+//! named presets and overrides whatever it wants to change:
+//!
+//! ```toml
+//! preset = "competitive"
+//! gravity = 30
+//! hold = "infinite"
+//! ```
+
+use std::{fs, io::ErrorKind, path::Path};
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use toml_edit::DocumentMut;
+
+use super::bitboard::{HEIGHT, WIDTH};
+
+/// where the rules are read from on launch
+pub const RULES_PATH: &str = "tetrust_rules.toml";
+
+/// names of every preset, see `Rules::preset`
+pub const PRESETS: &[&str] = &["default", "competitive", "classic"];
+
+/// When a piece can be put in hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HoldRule {
+    Off,
+    /// once per piece, until it locks
+    Once,
+    /// as often as you like
+    Infinite,
+}
+
+/// How the upcoming pieces are picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Randomizer {
+    /// every piece once in each shuffled group of seven
+    #[default]
+    Bag,
+    /// any piece at any time
+    Random,
+}
+
+/// How a piece turns half way round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kicks180 {
+    /// no half turns, only quarter ones
+    Off,
+    /// the same nudges a quarter turn tries
+    Nudge,
+    /// TETR.IO's SRS+ table, see `BitBoard::rotate_180`
+    #[default]
+    SrsPlus,
+}
+
+/// Timings are in ticks, see `clock::TICK_RATE`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    /// ticks for the piece to fall a row
+    pub gravity: u32,
+    /// ticks a piece can rest on the stack before it locks
+    pub lock_delay: u32,
+    /// moves and rotations that start the lock delay over, per piece
+    pub lock_resets: u32,
+    /// ticks a direction has to be held before the piece starts sliding
+    pub das: u32,
+    /// ticks between moves while sliding, 0 slides straight to the wall
+    pub arr: u32,
+    /// how many times faster soft drop is than gravity, 0 drops straight down
+    pub sdf: u32,
+    /// how many upcoming pieces are shown to players and bots
+    pub queue: usize,
+    pub hold: HoldRule,
+    pub randomizer: Randomizer,
+    // rules saved before half turns had their own kicks don't have it
+    #[serde(default)]
+    pub kicks_180: Kicks180,
+    /// initial rotation, a rotation held down as a piece spawns turns it straight away
+    #[serde(default)]
+    pub irs: bool,
+    /// initial hold, a hold held down as a piece spawns swaps it straight away
+    #[serde(default)]
+    pub ihs: bool,
+    /// ticks between a piece locking and the next one spawning
+    #[serde(default)]
+    pub are: u32,
+    /// ticks the full rows stay on the board before they're cleared, on top of `are`
+    #[serde(default)]
+    pub line_clear_delay: u32,
+    pub width: usize,
+    pub height: usize,
+    /// ticks between two inputs played by autoplay
+    pub autoplay_speed: u32,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            gravity: 60,
+            lock_delay: 30,
+            lock_resets: 15,
+            das: 10,
+            arr: 2,
+            sdf: 12,
+            queue: 5,
+            hold: HoldRule::Once,
+            randomizer: Randomizer::Bag,
+            kicks_180: Kicks180::SrsPlus,
+            irs: false,
+            ihs: false,
+            are: 0,
+            line_clear_delay: 0,
+            width: WIDTH,
+            height: HEIGHT,
+            autoplay_speed: 6,
+        }
+    }
+}
+
+/// a rules file, any field left out comes from the preset
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    preset: Option<String>,
+    gravity: Option<u32>,
+    lock_delay: Option<u32>,
+    lock_resets: Option<u32>,
+    das: Option<u32>,
+    arr: Option<u32>,
+    sdf: Option<u32>,
+    queue: Option<usize>,
+    hold: Option<HoldRule>,
+    randomizer: Option<Randomizer>,
+    kicks_180: Option<Kicks180>,
+    irs: Option<bool>,
+    ihs: Option<bool>,
+    are: Option<u32>,
+    line_clear_delay: Option<u32>,
+    width: Option<usize>,
+    height: Option<usize>,
+    autoplay_speed: Option<u32>,
+}
+
+impl Rules {
+    /// the rules by one of the names in [`PRESETS`]
+    pub fn preset(name: &str) -> Option<Self> {
+        let default = Self::default();
+        match name {
+            "default" => Some(default),
+            // fast handling, no waiting on soft drop, initial rotation and hold
+            "competitive" => Some(Self {
+                das: 7,
+                arr: 0,
+                sdf: 0,
+                irs: true,
+                ihs: true,
+                ..default
+            }),
+            // no hold, one piece of preview, no lock delay to speak of, no half turns, a pause
+            // before every piece and a longer one for clears
+            "classic" => Some(Self {
+                gravity: 48,
+                lock_delay: 0,
+                lock_resets: 0,
+                das: 16,
+                arr: 6,
+                sdf: 2,
+                queue: 1,
+                hold: HoldRule::Off,
+                randomizer: Randomizer::Random,
+                kicks_180: Kicks180::Off,
+                are: 10,
+                line_clear_delay: 20,
+                ..default
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).with_context(|| format!("in {}", path.display()))
+    }
+
+    /// Writes the rules that are different from `before` into the rules file, over what it
+    /// already has, so its preset, comments and other overrides stay as they were. A file that
+    /// isn't valid TOML is left alone.
+    pub fn save_changes(&self, before: &Rules, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut file: DocumentMut = text
+            .parse()
+            .with_context(|| format!("in {}", path.display()))?;
+
+        let rules: toml::Table = toml::to_string(self)?.parse()?;
+        let before: toml::Table = toml::to_string(before)?.parse()?;
+        for (key, value) in rules {
+            if before.get(&key) != Some(&value) {
+                file[&key] = toml_edit::value(value.to_string().parse::<toml_edit::Value>()?);
+            }
+        }
+        fs::write(path, file.to_string())?;
+        Ok(())
+    }
+
+    /// reads the rules from the text of a rules file and checks them
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let file: File = toml::from_str(text)?;
+        let mut rules = match &file.preset {
+            Some(name) => Self::preset(name).with_context(|| {
+                format!(
+                    "unknown preset {}, expected one of {}",
+                    name,
+                    PRESETS.join(", ")
+                )
+            })?,
+            None => Self::default(),
+        };
+
+        let File {
+            preset: _,
+            gravity,
+            lock_delay,
+            lock_resets,
+            das,
+            arr,
+            sdf,
+            queue,
+            hold,
+            randomizer,
+            kicks_180,
+            irs,
+            ihs,
+            are,
+            line_clear_delay,
+            width,
+            height,
+            autoplay_speed,
+        } = file;
+        rules.gravity = gravity.unwrap_or(rules.gravity);
+        rules.lock_delay = lock_delay.unwrap_or(rules.lock_delay);
+        rules.lock_resets = lock_resets.unwrap_or(rules.lock_resets);
+        rules.das = das.unwrap_or(rules.das);
+        rules.arr = arr.unwrap_or(rules.arr);
+        rules.sdf = sdf.unwrap_or(rules.sdf);
+        rules.queue = queue.unwrap_or(rules.queue);
+        rules.hold = hold.unwrap_or(rules.hold);
+        rules.randomizer = randomizer.unwrap_or(rules.randomizer);
+        rules.kicks_180 = kicks_180.unwrap_or(rules.kicks_180);
+        rules.irs = irs.unwrap_or(rules.irs);
+        rules.ihs = ihs.unwrap_or(rules.ihs);
+        rules.are = are.unwrap_or(rules.are);
+        rules.line_clear_delay = line_clear_delay.unwrap_or(rules.line_clear_delay);
+        rules.width = width.unwrap_or(rules.width);
+        rules.height = height.unwrap_or(rules.height);
+        rules.autoplay_speed = autoplay_speed.unwrap_or(rules.autoplay_speed);
+
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// checks the values make sense, naming the first one that doesn't
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.gravity == 0 {
+            bail!("gravity must be at least 1 tick");
+        }
+        if self.autoplay_speed == 0 {
+            bail!("autoplay_speed must be at least 1 tick");
+        }
+        if self.queue > 7 {
+            bail!("queue can show at most 7 pieces, not {}", self.queue);
+        }
+        // the board, the bots and the renderer are all built around the standard size so far
+        if (self.width, self.height) != (WIDTH, HEIGHT) {
+            bail!(
+                "only {}x{} boards are supported, not {}x{}",
+                WIDTH,
+                HEIGHT,
+                self.width,
+                self.height
+            );
+        }
+        Ok(())
+    }
+
+    /// ticks for the piece to fall a row while soft dropping, 0 if it drops straight down
+    pub fn soft_gravity(&self) -> u32 {
+        match self.sdf {
+            0 => 0,
+            sdf => (self.gravity / sdf).max(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_the_preset() {
+        let rules =
+            Rules::parse("preset = \"classic\"\ngravity = 30\nhold = \"infinite\"").unwrap();
+        assert_eq!(rules.gravity, 30);
+        assert_eq!(rules.hold, HoldRule::Infinite);
+        // the rest come from the preset
+        assert_eq!(rules.queue, 1);
+        assert_eq!(rules.are, 10);
+        assert_eq!(Rules::parse("").unwrap(), Rules::default());
+    }
+
+    #[test]
+    fn rejects_bad_rules() {
+        assert!(Rules::parse("preset = \"modern\"").is_err());
+        assert!(Rules::parse("speed = 3").is_err());
+        assert!(Rules::parse("gravity = 0").is_err());
+        assert!(Rules::parse("queue = 8").is_err());
+        assert!(Rules::parse("width = 12").is_err());
+        assert!(Rules::parse("kicks_180 = \"srs\"").is_err());
+    }
+
+    #[test]
+    fn saves_only_the_changes() {
+        let path = std::env::temp_dir().join("tetrust_rules_saves_only_the_changes.toml");
+        let text = "# faster\npreset = \"competitive\"\ngravity = 30\n";
+        fs::write(&path, text).unwrap();
+
+        let before = Rules::load(&path).unwrap();
+        let rules = Rules { das: 5, ..before };
+        rules.save_changes(&before, &path).unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(saved, format!("{}das = 5\n", text));
+        assert_eq!(Rules::parse(&saved).unwrap(), rules);
+    }
+
+    #[test]
+    fn leaves_invalid_files_alone() {
+        let path = std::env::temp_dir().join("tetrust_rules_leaves_invalid_files_alone.toml");
+        fs::write(&path, "gravity = ").unwrap();
+
+        let rules = Rules::default();
+        let result = Rules { das: 5, ..rules }.save_changes(&rules, &path);
+
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+        assert_eq!(saved, "gravity = ");
+    }
+}
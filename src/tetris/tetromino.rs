@@ -1,16 +1,19 @@
 pub mod tetromino_kind;
 
+use serde::{Deserialize, Serialize};
 use tetromino_kind::TetrominoKind;
 
 use super::point::Point;
 
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Tetromino {
     pub points: [Point<f32>; 4],
     pub anchor: Point<f32>,
-    pub color: [f32; 3],
 
     pub kind: TetrominoKind,
+    /// quarter turns clockwise from the spawn orientation, 0 to 3
+    #[serde(default)]
+    pub rotation: u8,
 }
 
 impl Tetromino {
@@ -24,8 +27,8 @@ impl Tetromino {
                     Point::new(1.5, -0.5),
                 ],
                 anchor: Point::new(4.5, 0.5),
-                color: [0.19, 0.65, 0.80],
                 kind,
+                rotation: 0,
             },
             TetrominoKind::O => Self {
                 points: [
@@ -35,8 +38,8 @@ impl Tetromino {
                     Point::new(0.5, 0.5),
                 ],
                 anchor: Point::new(4.5, 0.5),
-                color: [0.80, 0.70, 0.03],
                 kind,
+                rotation: 0,
             },
             TetrominoKind::S => Self {
                 points: [
@@ -46,8 +49,8 @@ impl Tetromino {
                     Point::new(-1., 0.),
                 ],
                 anchor: Point::new(4., 0.),
-                color: [0.26, 0.71, 0.26],
                 kind,
+                rotation: 0,
             },
             TetrominoKind::Z => Self {
                 points: [
@@ -57,8 +60,8 @@ impl Tetromino {
                     Point::new(1., 0.),
                 ],
                 anchor: Point::new(4., 0.),
-                color: [0.80, 0.13, 0.16],
                 kind,
+                rotation: 0,
             },
             TetrominoKind::J => Self {
                 points: [
@@ -68,8 +71,8 @@ impl Tetromino {
                     Point::new(1., 0.),
                 ],
                 anchor: Point::new(4., 0.),
-                color: [0.35, 0.4, 0.68],
                 kind,
+                rotation: 0,
             },
             TetrominoKind::L => Self {
                 points: [
@@ -79,8 +82,8 @@ impl Tetromino {
                     Point::new(1., 0.),
                 ],
                 anchor: Point::new(4., 0.),
-                color: [0.80, 0.40, 0.10],
                 kind,
+                rotation: 0,
             },
             TetrominoKind::T => Self {
                 points: [
@@ -90,15 +93,22 @@ impl Tetromino {
                     Point::new(1., 0.),
                 ],
                 anchor: Point::new(4., 0.),
-                color: [0.68, 0.3, 0.61],
                 kind,
+                rotation: 0,
             },
         }
     }
 
     pub fn rotate(&mut self, radians: f32) {
-        let sin = radians.sin();
-        let cos = radians.cos();
+        let quarters = (radians / std::f32::consts::FRAC_PI_2).round() as i32;
+        self.rotation = (self.rotation as i32 + quarters).rem_euclid(4) as u8;
+
+        // whole quarter turns, which is every rotation in the game, skip the trig
+        let (sin, cos) = if (radians - quarters as f32 * std::f32::consts::FRAC_PI_2).abs() < 1e-4 {
+            [(0., 1.), (1., 0.), (0., -1.), (-1., 0.)][quarters.rem_euclid(4) as usize]
+        } else {
+            radians.sin_cos()
+        };
 
         for point in self.points.iter_mut() {
             let x = point.x;
@@ -110,14 +120,16 @@ impl Tetromino {
     }
 
     pub fn get_points_vec(&self) -> Vec<Point<isize>> {
-        self.points
-            .iter()
-            .map(|x| {
-                Point::new(
-                    (x.x + self.anchor.x) as isize,
-                    (x.y + self.anchor.y) as usize as isize,
-                )
-            })
-            .collect::<Vec<Point<isize>>>()
+        self.cells().to_vec()
+    }
+
+    /// the board cells the tetromino covers
+    pub fn cells(&self) -> [Point<isize>; 4] {
+        self.points.map(|x| {
+            Point::new(
+                (x.x + self.anchor.x) as isize,
+                (x.y + self.anchor.y) as usize as isize,
+            )
+        })
     }
 }
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TetrominoKind {
+    #[default]
+    I,
+    O,
+    S,
+    Z,
+    J,
+    L,
+    T,
+}
+
+impl From<u8> for TetrominoKind {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::I,
+            1 => Self::O,
+            2 => Self::S,
+            3 => Self::Z,
+            4 => Self::J,
+            5 => Self::L,
+            6 => Self::T,
+            _ => panic!("Cant convert to tetromino type"),
+        }
+    }
+}
+
+/// the piece's letter, either case
+impl TryFrom<char> for TetrominoKind {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value.to_ascii_uppercase() {
+            'I' => Self::I,
+            'O' => Self::O,
+            'S' => Self::S,
+            'Z' => Self::Z,
+            'J' => Self::J,
+            'L' => Self::L,
+            'T' => Self::T,
+            _ => anyhow::bail!("{} isn't a piece", value),
+        })
+    }
+}
@@ -0,0 +1,19 @@
+use super::{
+    board::Board,
+    rules::Kicks180,
+    tetromino::{Tetromino, tetromino_kind::TetrominoKind},
+};
+
+/// Everything a player can see about a game: the board, the falling piece, hold and the next
+/// queue. Bots plan from this instead of from the game itself.
+#[derive(Debug, Clone)]
+pub struct GameView<'a> {
+    pub board: &'a Board,
+    pub piece: Tetromino,
+    pub hold: Option<TetrominoKind>,
+    /// false once hold has been used for the current piece
+    pub can_hold: bool,
+    pub queue: Vec<TetrominoKind>,
+    /// how the piece turns half way round, which is up to the rules
+    pub kicks_180: Kicks180,
+}